@@ -0,0 +1,30 @@
+// Links the web app manifest, registers the service worker and keeps the install prompt until the user asks for it
+const manifestLink = document.createElement("link");
+manifestLink.rel = "manifest";
+manifestLink.href = "/favicon_io/site.webmanifest";
+document.head.appendChild(manifestLink);
+
+if ("serviceWorker" in navigator) {
+    window.addEventListener("load", () => {
+        navigator.serviceWorker.register("/sw.js").catch((err) => console.warn("service worker registration failed", err));
+    });
+}
+
+let deferredInstallPrompt = null;
+window.addEventListener("beforeinstallprompt", (event) => {
+    event.preventDefault();
+    deferredInstallPrompt = event;
+    window.dispatchEvent(new Event("equaio-installable"));
+});
+window.addEventListener("appinstalled", () => {
+    deferredInstallPrompt = null;
+});
+
+window.equaioCanInstall = () => deferredInstallPrompt !== null;
+window.equaioInstall = async () => {
+    if (!deferredInstallPrompt) { return false; }
+    deferredInstallPrompt.prompt();
+    const { outcome } = await deferredInstallPrompt.userChoice;
+    deferredInstallPrompt = null;
+    return outcome === "accepted";
+};
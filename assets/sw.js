@@ -0,0 +1,89 @@
+// Service worker of the equaio web app, makes the app usable offline after the first load.
+// Bump the version when the list of precached files changes.
+const CACHE_NAME = "equaio-v1";
+
+const PRECACHE_URLS = [
+    "/",
+    "/index.html",
+    "/main.css",
+    "/block.css",
+    "/worksheet.css",
+    "/pwa.js",
+    "/LatinmodernmathRegular.woff",
+    "/equaio.png",
+    "/favicon.ico",
+    "/favicon_io/site.webmanifest",
+    "/favicon_io/android-chrome-192x192.png",
+    "/favicon_io/android-chrome-512x512.png",
+    // wasm bundle, as built by `dx build`
+    "/assets/dioxus/equaio-dx.js",
+    "/assets/dioxus/equaio-dx_bg.wasm",
+];
+
+// the js and the wasm of the bundle only work together, they are updated as a pair when the page is loaded
+const BUNDLE_URLS = ["/assets/dioxus/equaio-dx.js", "/assets/dioxus/equaio-dx_bg.wasm"];
+let bundleUpdate = Promise.resolve();
+
+// replace the cached bundle only when both files arrive, a failed update keeps the previous pair
+function updateBundle() {
+    return Promise.all(BUNDLE_URLS.map((url) => fetch(url, { cache: "no-cache" }).then((response) => {
+        if (!response.ok) { throw new Error(`failed to fetch ${url}`); }
+        return response;
+    })))
+        .then((responses) => caches.open(CACHE_NAME).then((cache) =>
+            Promise.all(responses.map((response, i) => cache.put(BUNDLE_URLS[i], response)))))
+        .catch((err) => console.warn(`kept the cached bundle: ${err}`));
+}
+
+self.addEventListener("install", (event) => {
+    // a missing file should not prevent the others from being cached
+    event.waitUntil(caches.open(CACHE_NAME).then((cache) => Promise.all(
+        PRECACHE_URLS.map((url) => cache.add(url).catch(() => console.warn(`failed to precache ${url}`)))
+    )));
+    self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(caches.keys().then((keys) => Promise.all(
+        keys.filter((key) => key !== CACHE_NAME).map((key) => caches.delete(key))
+    )));
+    self.clients.claim();
+});
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) { return; }
+    
+    if (request.mode === "navigate") {
+        // network first, so a deployed update is picked up when online
+        event.respondWith(fetch(request)
+            .then((response) => {
+                const copy = response.clone();
+                caches.open(CACHE_NAME).then((cache) => cache.put("/index.html", copy));
+                bundleUpdate = updateBundle();
+                event.waitUntil(bundleUpdate);
+                return response;
+            })
+            .catch(() => caches.match("/index.html")));
+        return;
+    }
+    
+    if (BUNDLE_URLS.includes(new URL(request.url).pathname)) {
+        // always the cached pair, once the update started by the page is done
+        event.respondWith(bundleUpdate
+            .then(() => caches.match(request))
+            .then((cached) => cached || fetch(request)));
+        return;
+    }
+    
+    // stale while revalidate, also caches files that are not precached (e.g. content packs)
+    event.respondWith(caches.open(CACHE_NAME).then((cache) => cache.match(request).then((cached) => {
+        const fetched = fetch(request).then((response) => {
+            if (response.ok) { cache.put(request, response.clone()); }
+            return response;
+        });
+        if (!cached) { return fetched; }
+        fetched.catch(() => {});
+        return cached;
+    })));
+});
@@ -0,0 +1,26 @@
+#![allow(clippy::needless_return)]
+// Fails the build when a bundled ruleset does not parse, naming the rule and field at fault
+#[allow(dead_code)]
+#[path = "src/json.rs"]
+mod json;
+#[path = "src/extends.rs"]
+mod extends;
+#[path = "src/validate.rs"]
+mod validate;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/json.rs");
+    println!("cargo:rerun-if-changed=src/extends.rs");
+    println!("cargo:rerun-if-changed=src/validate.rs");
+    let lookup = |name: &str| json::RULESETS.iter().find(|(n, _)| *n == name).map(|(_, rulestr)| rulestr.to_string());
+    let mut errors = vec![];
+    for (name, _) in json::RULESETS {
+        match extends::resolve_ruleset(name, &lookup) {
+            Ok(rulestr) => errors.extend(validate::validate_ruleset(name, &rulestr).iter().map(|err| err.to_string())),
+            Err(err) => errors.push(err),
+        }
+    }
+    if errors.is_empty() { return; }
+    for err in &errors { eprintln!("error: {}", err); }
+    panic!("{} error(s) in the bundled rulesets, see above", errors.len());
+}
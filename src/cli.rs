@@ -0,0 +1,303 @@
+//! Headless runner for problems and scripted actions, built with the `cli` feature
+use equaio::block::{Block, BlockContext};
+use equaio::expression::Address;
+use serde::{Deserialize, Serialize};
+use super::content::ContentStore;
+use super::presentation::block_to_text;
+use super::session;
+use super::worksheet;
+
+const USAGE: &str = "\
+usage: equaio-dx --problem <id> [--pack <content_pack.json>]... [--script <script.json>] [--format text|json]
+
+The script is a json list of steps, e.g.
+    [{ \"seq\": 0, \"select\": [{ \"path\": [], \"sub\": 0 }], \"action\": \"Addition with 0\" },
+     { \"select\": [{ \"path\": [0, 1] }], \"action\": 0 }]
+where `select` are addresses of symbols in the last line (listed by `--format text` as `symbol@path` or `symbol@path#sub`)
+and `action` is either the index or the label of a possible action.";
+
+/// The address of a symbol, `sub` is the index of an operator among the operators of an n-ary expression
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ScriptAddress {
+    path: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sub: Option<usize>,
+}
+
+impl ScriptAddress {
+    fn from_address(address: &Address) -> Self {
+        ScriptAddress { path: address.path.clone(), sub: address.sub }
+    }
+    fn to_address(&self) -> Address {
+        Address::new(self.path.clone(), self.sub)
+    }
+}
+
+impl std::fmt::Display for ScriptAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let path = self.path.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        write!(f, "[{}]", path)?;
+        if let Some(sub) = self.sub { write!(f, "#{}", sub)?; }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ScriptStep {
+    #[serde(default)]
+    seq: usize,
+    #[serde(default)]
+    select: Vec<ScriptAddress>,
+    action: ScriptAction,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum ScriptAction {
+    Index(usize),
+    Label(String),
+}
+
+#[derive(Serialize, Debug)]
+struct LineOutput {
+    action: String,
+    expr: String,
+    is_auto_generated: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct SymbolOutput {
+    symbol: String,
+    address: ScriptAddress,
+}
+
+#[derive(Serialize, Debug)]
+struct SequenceOutput {
+    lines: Vec<LineOutput>,
+    /// symbols of the last line with the addresses used by `select`
+    symbols: Vec<SymbolOutput>,
+}
+
+#[derive(Serialize, Debug)]
+struct DerivationOutput {
+    problem_id: String,
+    label: String,
+    sequences: Vec<SequenceOutput>,
+}
+
+#[derive(Debug, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    problem_id: String,
+    packs: Vec<String>,
+    script: Option<String>,
+    format: OutputFormat,
+}
+
+/// What the command line asks for
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run(Args),
+    Help,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Command, String> {
+    let mut problem_id = None;
+    let mut packs = vec![];
+    let mut script = None;
+    let mut format = OutputFormat::Text;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}\n{}", arg, USAGE));
+        match arg.as_str() {
+            "--problem" => problem_id = Some(value()?),
+            "--pack" => packs.push(value()?),
+            "--script" => script = Some(value()?),
+            "--format" => format = match value()?.as_str() {
+                "text" => OutputFormat::Text,
+                "json" => OutputFormat::Json,
+                other => return Err(format!("unknown format: {}\n{}", other, USAGE)),
+            },
+            "--help" | "-h" => return Ok(Command::Help),
+            other => return Err(format!("unknown argument: {}\n{}", other, USAGE)),
+        }
+    }
+    let problem_id = problem_id.ok_or(USAGE.to_string())?;
+    return Ok(Command::Run(Args { problem_id, packs, script, format }));
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| format!("failed to read {}: {}", path, err))?;
+    return serde_json::from_str(&contents).map_err(|err| format!("failed to parse {}: {}", path, err));
+}
+
+fn apply_step(ws: &mut equaio::worksheet::Worksheet, step: &ScriptStep, block_ctx: &BlockContext) -> Result<(), String> {
+    let mut seq = ws.get(step.seq).ok_or(format!("there is no expression sequence {}", step.seq))?;
+    let symbols = seq.history.last()
+        .map(|line| session::symbol_addresses(&Block::from_root_expression(&line.expr, block_ctx)))
+        .unwrap_or_default();
+    let addresses = step.select.iter().map(ScriptAddress::to_address).collect::<Vec<_>>();
+    if let Some((missing, _)) = step.select.iter().zip(&addresses).find(|(_, addr)| !symbols.contains(addr)) {
+        return Err(format!("there is no symbol at {} in the last line", missing));
+    }
+    let labels = seq.get_possible_actions(&addresses).iter()
+        .map(|(action, _)| action.to_string())
+        .collect::<Vec<_>>();
+    let action_index = match &step.action {
+        ScriptAction::Index(i) if *i < labels.len() => Some(*i),
+        ScriptAction::Index(_) => None,
+        ScriptAction::Label(label) => labels.iter().position(|l| l == label),
+    };
+    let Some(action_index) = action_index else {
+        return Err(format!("action {:?} is not possible, the possible actions are {:?}", step.action, labels));
+    };
+    
+    let history_len = seq.history.len();
+    seq.try_apply_action_by_index(&addresses, action_index);
+    if seq.history.len() <= history_len { return Err(format!("failed to apply \"{}\"", labels[action_index])); }
+    ws.store(step.seq, seq);
+    return Ok(());
+}
+
+fn to_text(derivation: &DerivationOutput) -> String {
+    let mut lines = vec![format!("{} ({})", derivation.label, derivation.problem_id)];
+    for (i, seq) in derivation.sequences.iter().enumerate() {
+        lines.push(format!("[{}]", i));
+        for line in &seq.lines {
+            let action = if line.is_auto_generated { format!("{} (auto)", line.action) } else { line.action.clone() };
+            lines.push(format!("    {:<40} {}", line.expr, action));
+        }
+        let symbols = seq.symbols.iter().map(|symbol| format!("{}@{}", symbol.symbol, symbol.address)).collect::<Vec<_>>();
+        lines.push(format!("    symbols: {}", symbols.join(" ")));
+    }
+    return lines.join("\n");
+}
+
+/// Apply `script` to the problem `problem_id` of `content`
+fn derive(content: &ContentStore, problem_id: &str, script: &[ScriptStep]) -> Result<DerivationOutput, String> {
+    let ws_data = content.problem(problem_id).cloned()
+        .ok_or(format!("problem not found: {}", problem_id))?;
+    let block_ctx = worksheet::get_presentation(content, &ws_data).block_context();
+    let mut ws = worksheet::init_worksheet(content, ws_data.clone());
+    for (i, step) in script.iter().enumerate() {
+        apply_step(&mut ws, step, &block_ctx).map_err(|err| format!("step {}: {}", i + 1, err))?;
+    }
+    
+    let sequences = (0..ws.len()).filter_map(|i| ws.get(i)).map(|seq| {
+        let blocks = seq.history.iter().map(|line| Block::from_root_expression(&line.expr, &block_ctx)).collect::<Vec<_>>();
+        let lines = seq.history.iter().zip(blocks.iter()).map(|(line, block)| LineOutput {
+            action: line.action.to_string(),
+            expr: block_to_text(block),
+            is_auto_generated: line.is_auto_generated,
+        }).collect();
+        let symbols = blocks.last()
+            .map(|block| session::symbol_blocks(block).iter().map(|symbol| SymbolOutput {
+                symbol: symbol.symbol.clone().unwrap_or_default(),
+                address: ScriptAddress::from_address(&symbol.address),
+            }).collect())
+            .unwrap_or_default();
+        SequenceOutput { lines, symbols }
+    }).collect();
+    return Ok(DerivationOutput { problem_id: problem_id.to_string(), label: ws_data.label, sequences });
+}
+
+fn try_run(args: Vec<String>) -> Result<String, String> {
+    let args = match parse_args(args)? {
+        Command::Run(args) => args,
+        Command::Help => return Ok(USAGE.to_string()),
+    };
+    let mut content = ContentStore::builtin();
+    for path in &args.packs { content.add_pack(read_json(path)?); }
+    let script: Vec<ScriptStep> = match &args.script {
+        Some(path) => read_json(path)?,
+        None => vec![],
+    };
+    let derivation = derive(&content, &args.problem_id, &script)?;
+    
+    return match args.format {
+        OutputFormat::Text => Ok(to_text(&derivation)),
+        OutputFormat::Json => serde_json::to_string_pretty(&derivation).map_err(|err| err.to_string()),
+    };
+}
+
+/// Run the cli with the arguments (without the program name), returns the exit code
+pub fn run(args: Vec<String>) -> i32 {
+    match try_run(args) {
+        Ok(output) => { println!("{}", output); 0 },
+        Err(err) => { eprintln!("{}", err); 1 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_reads_every_option() {
+        let parsed = parse_args(args(&["--problem", "algebra0", "--pack", "a.json", "--pack", "b.json", "--script", "s.json", "--format", "json"]));
+        assert_eq!(parsed, Ok(Command::Run(Args {
+            problem_id: "algebra0".to_string(),
+            packs: vec!["a.json".to_string(), "b.json".to_string()],
+            script: Some("s.json".to_string()),
+            format: OutputFormat::Json,
+        })));
+        assert!(matches!(parse_args(args(&["--problem", "algebra0"])), Ok(Command::Run(Args { format: OutputFormat::Text, .. }))));
+        assert_eq!(parse_args(args(&["--help"])), Ok(Command::Help));
+        assert_eq!(try_run(args(&["--problem", "algebra0", "-h"])), Ok(USAGE.to_string()));
+    }
+
+    #[test]
+    fn parse_args_rejects_invalid_arguments() {
+        assert!(parse_args(args(&[])).is_err());
+        assert!(parse_args(args(&["--problem"])).is_err_and(|err| err.starts_with("missing value for --problem")));
+        assert!(parse_args(args(&["--problem", "algebra0", "--format", "xml"])).is_err_and(|err| err.starts_with("unknown format: xml")));
+        assert!(parse_args(args(&["--problem", "algebra0", "--verbose"])).is_err_and(|err| err.starts_with("unknown argument: --verbose")));
+    }
+
+    #[test]
+    fn script_steps_select_addresses() {
+        let script: Vec<ScriptStep> = serde_json::from_str(r#"[
+            { "seq": 1, "select": [{ "path": [0, 1] }, { "path": [], "sub": 0 }], "action": "Addition with 0" },
+            { "action": 2 }
+        ]"#).unwrap();
+        assert_eq!(script[0].seq, 1);
+        assert_eq!(script[0].select, vec![
+            ScriptAddress { path: vec![0, 1], sub: None },
+            ScriptAddress { path: vec![], sub: Some(0) },
+        ]);
+        assert!(matches!(&script[0].action, ScriptAction::Label(label) if label == "Addition with 0"));
+        assert!(script[1].select.is_empty());
+        assert!(matches!(script[1].action, ScriptAction::Index(2)));
+    }
+
+    #[test]
+    fn derive_applies_a_step_at_an_address() {
+        let content = ContentStore::builtin();
+        let initial = derive(&content, "algebra4", &[]).unwrap();
+        let times = initial.sequences[0].symbols.iter().find(|symbol| symbol.symbol == "*").unwrap().address.clone();
+        let step = ScriptStep { seq: 0, select: vec![times], action: ScriptAction::Label("Product of powers".to_string()) };
+        let derivation = derive(&content, "algebra4", &[step]).unwrap();
+        let lines = &derivation.sequences[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].action, "Product of powers");
+        assert!(!lines[1].expr.contains('*'));
+    }
+
+    #[test]
+    fn derive_reports_the_failing_step() {
+        let content = ContentStore::builtin();
+        let step = ScriptStep { seq: 0, select: vec![ScriptAddress { path: vec![9, 9], sub: None }], action: ScriptAction::Index(0) };
+        let err = derive(&content, "algebra5", &[step]).err().unwrap();
+        assert_eq!(err, "step 1: there is no symbol at [9,9] in the last line");
+        assert!(derive(&content, "missing", &[]).is_err_and(|err| err == "problem not found: missing"));
+    }
+}
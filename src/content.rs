@@ -0,0 +1,361 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+#[cfg(not(feature = "cli"))]
+use dioxus::prelude::*;
+use dioxus_logger::tracing::warn;
+use equaio::rule::RuleSet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::course::CourseData;
+use super::json;
+use super::extends;
+use super::presentation::Presentation;
+#[cfg(not(feature = "cli"))]
+use super::storage;
+use super::validate;
+use super::worksheet::WorksheetData;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CategoryData {
+    pub name: String,
+    pub problem_ids: Vec<String>,
+}
+
+/// Categories and courses and the problems they refer to, either bundled or opened from a file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ContentPack {
+    #[serde(default)]
+    pub courses: Vec<CourseData>,
+    pub categories: Vec<CategoryData>,
+    pub problems: HashMap<String, WorksheetData>,
+}
+
+impl ContentPack {
+    pub fn builtin() -> Self {
+        let categories: Result<Vec<CategoryData>, _> = serde_json::from_str(json::MAIN_MENU_DATA);
+        let problems: Result<HashMap<String, WorksheetData>, _> = serde_json::from_str(json::PROBLEMS_DATA_MAP);
+        let courses: Result<Vec<CourseData>, _> = serde_json::from_str(json::COURSES_DATA);
+        ContentPack { courses: courses.unwrap_or_default(), categories: categories.unwrap_or_default(), problems: problems.unwrap_or_default() }
+    }
+    pub fn extend(&mut self, other: ContentPack) {
+        self.courses.extend(other.courses);
+        self.categories.extend(other.categories);
+        self.problems.extend(other.problems);
+    }
+}
+
+const DEFAULT_RULESET: &str = "algebra";
+#[cfg(not(feature = "cli"))]
+const LOCAL_PACK_STORAGE_KEY: &str = "equaio-local-pack";
+#[cfg(not(feature = "cli"))]
+const LOCAL_RULESETS_STORAGE_KEY: &str = "equaio-local-rulesets";
+/// ruleset being edited in the ruleset editor, not listed with the other rulesets
+pub const SCRATCH_RULESET_NAME: &str = "scratch";
+/// category of the problems made in the problem editor
+#[cfg(not(feature = "cli"))]
+pub const LOCAL_CATEGORY_NAME: &str = "My problems";
+
+/// The bundled content merged with the content packs opened by the user,
+/// and the local problems and rulesets of the problem and ruleset editors.
+/// Packs are parsed when they are added, rulesets and their presentation on first use.
+#[derive(Clone)]
+pub struct ContentStore {
+    content: ContentPack,
+    local: ContentPack,
+    ruleset_sources: HashMap<String, String>,
+    /// the local rulesets as saved, which can extend bundled and other local rulesets
+    #[cfg(not(feature = "cli"))]
+    local_rulesets: HashMap<String, String>,
+    /// the local rulesets with their `extends` resolved
+    local_ruleset_sources: HashMap<String, String>,
+    /// what is wrong with the local rulesets that are left out of `local_ruleset_sources`
+    #[cfg(not(feature = "cli"))]
+    local_ruleset_errors: HashMap<String, Vec<String>>,
+    scratch_ruleset: String,
+    rulesets: Rc<RefCell<HashMap<String, RuleSet>>>,
+    presentations: Rc<RefCell<HashMap<String, Presentation>>>,
+}
+
+impl ContentStore {
+    pub fn builtin() -> Self {
+        ContentStore {
+            content: ContentPack::builtin(),
+            local: ContentPack::default(),
+            ruleset_sources: builtin_ruleset_sources(),
+            #[cfg(not(feature = "cli"))]
+            local_rulesets: HashMap::new(),
+            local_ruleset_sources: HashMap::new(),
+            #[cfg(not(feature = "cli"))]
+            local_ruleset_errors: HashMap::new(),
+            scratch_ruleset: String::new(),
+            rulesets: Default::default(),
+            presentations: Default::default(),
+        }
+    }
+    pub fn add_pack(&mut self, pack: ContentPack) {
+        let courses = pack.courses.clone();
+        self.content.extend(pack);
+        // a course referring to a missing problem or prerequisite leaves a lesson locked forever
+        for course in &courses {
+            for err in course.validate(|id| self.problem(id).is_some()) { warn!("{}", err); }
+        }
+    }
+
+    #[cfg(not(feature = "cli"))]
+    pub fn courses(&self) -> impl Iterator<Item = &CourseData> {
+        self.content.courses.iter()
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn course(&self, name: &str) -> Option<&CourseData> {
+        self.courses().find(|course| course.name == name)
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn categories(&self) -> impl Iterator<Item = &CategoryData> {
+        self.content.categories.iter().chain(self.local.categories.iter())
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn category(&self, name: &str) -> Option<&CategoryData> {
+        self.categories().find(|cat| cat.name == name)
+    }
+    pub fn problem(&self, problem_id: &str) -> Option<&WorksheetData> {
+        self.content.problems.get(problem_id).or_else(|| self.local.problems.get(problem_id))
+    }
+    /// Whether `problem_id` is a bundled problem or comes from an opened content pack
+    #[cfg(not(feature = "cli"))]
+    pub fn is_readonly_problem(&self, problem_id: &str) -> bool {
+        self.content.problems.contains_key(problem_id)
+    }
+
+    #[cfg(not(feature = "cli"))]
+    pub fn local_pack(&self) -> &ContentPack {
+        &self.local
+    }
+    /// Add or replace a problem of the local pack, and persist the pack
+    #[cfg(not(feature = "cli"))]
+    pub fn save_local_problem(&mut self, problem_id: String, ws_data: WorksheetData) {
+        if self.local.categories.is_empty() {
+            self.local.categories.push(CategoryData { name: LOCAL_CATEGORY_NAME.to_string(), problem_ids: vec![] });
+        }
+        let problem_ids = &mut self.local.categories[0].problem_ids;
+        if !problem_ids.contains(&problem_id) { problem_ids.push(problem_id.clone()); }
+        self.local.problems.insert(problem_id, ws_data);
+        if let Ok(json) = serde_json::to_string(&self.local) { storage::save(LOCAL_PACK_STORAGE_KEY, &json); }
+    }
+    /// Problems of the category named `name`, in order
+    #[cfg(not(feature = "cli"))]
+    pub fn category_problems(&self, name: &str) -> Vec<(String, WorksheetData)> {
+        let problem_ids = self.category(name).map(|cat| cat.problem_ids.clone()).unwrap_or_default();
+        return problem_ids.into_iter()
+            .filter_map(|id| self.problem(&id).cloned().map(|ws_data| (id, ws_data)))
+            .collect();
+    }
+
+    #[cfg(not(feature = "cli"))]
+    pub fn ruleset_names(&self) -> Vec<String> {
+        let mut names = self.ruleset_sources.keys().chain(self.local_rulesets.keys()).cloned().collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        return names;
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn is_builtin_ruleset(&self, name: &str) -> bool {
+        self.ruleset_sources.contains_key(name)
+    }
+    /// The json of the ruleset named `name`, unknown names fall back to the algebra ruleset
+    pub fn ruleset_source(&self, name: &str) -> &str {
+        if name == SCRATCH_RULESET_NAME { return &self.scratch_ruleset; }
+        self.ruleset_sources.get(name)
+            .or_else(|| self.local_ruleset_sources.get(name))
+            .or_else(|| self.ruleset_sources.get(DEFAULT_RULESET))
+            .map(|source| source.as_str()).unwrap_or_default()
+    }
+    /// Replace the ruleset tried out in the ruleset editor, `rulestr` must be a valid ruleset
+    #[cfg(not(feature = "cli"))]
+    pub fn set_scratch_ruleset(&mut self, rulestr: String) {
+        self.scratch_ruleset = rulestr;
+        self.invalidate_ruleset(SCRATCH_RULESET_NAME);
+    }
+    /// The ruleset that the local ruleset named `name` extends, as saved
+    #[cfg(not(feature = "cli"))]
+    pub fn local_ruleset_extends(&self, name: &str) -> Option<String> {
+        let value: Value = serde_json::from_str(self.local_rulesets.get(name)?).ok()?;
+        return value.get("extends").and_then(|base| base.as_str()).map(|base| base.to_string());
+    }
+    /// Add or replace a ruleset made in the ruleset editor, and persist the local rulesets
+    #[cfg(not(feature = "cli"))]
+    pub fn save_local_ruleset(&mut self, name: String, rulestr: String) {
+        self.local_rulesets.insert(name, rulestr);
+        self.resolve_local_rulesets();
+        if let Ok(json) = serde_json::to_string(&self.local_rulesets) { storage::save(LOCAL_RULESETS_STORAGE_KEY, &json); }
+    }
+    /// What is wrong with the saved local ruleset named `name`, empty if it is used
+    #[cfg(not(feature = "cli"))]
+    pub fn local_ruleset_errors(&self, name: &str) -> &[String] {
+        self.local_ruleset_errors.get(name).map(|errors| errors.as_slice()).unwrap_or_default()
+    }
+    /// Resolve the `extends` of every local ruleset.
+    /// A local ruleset that does not resolve or is invalid, e.g. saved by an older version of the editor, is left out.
+    #[cfg(not(feature = "cli"))]
+    fn resolve_local_rulesets(&mut self) {
+        let lookup = |name: &str| builtin_ruleset_json(name).or_else(|| self.local_rulesets.get(name).cloned());
+        let mut sources = HashMap::new();
+        let mut errors = HashMap::new();
+        for name in self.local_rulesets.keys() {
+            let resolved = extends::resolve_ruleset(name, &lookup).map_err(|err| vec![err]).and_then(|source| {
+                let errors = validate::validate_ruleset(name, &source).iter().map(|err| err.to_string()).collect::<Vec<_>>();
+                if errors.is_empty() { Ok(source) } else { Err(errors) }
+            });
+            match resolved {
+                Ok(source) => { sources.insert(name.clone(), source); },
+                Err(ruleset_errors) => {
+                    for err in &ruleset_errors { warn!("{}", err); }
+                    errors.insert(name.clone(), ruleset_errors);
+                },
+            }
+        }
+        // a ruleset extending the one that changed changes with it
+        for name in self.local_rulesets.keys() { self.invalidate_ruleset(name); }
+        self.local_ruleset_sources = sources;
+        self.local_ruleset_errors = errors;
+    }
+    #[cfg(not(feature = "cli"))]
+    fn invalidate_ruleset(&self, name: &str) {
+        let restricted_prefix = format!("{}|", name);
+        self.rulesets.borrow_mut().retain(|key, _| key != name && !key.starts_with(&restricted_prefix));
+        self.presentations.borrow_mut().remove(name);
+    }
+    /// The parsed ruleset cached as `key`, the default ruleset if it does not parse
+    fn cached_ruleset(&self, key: &str, rulestr: impl FnOnce() -> String) -> RuleSet {
+        if let Some(ruleset) = self.rulesets.borrow().get(key) { return ruleset.clone(); }
+        let rulestr = rulestr();
+        // the bundled rulesets are checked by the build script and the local ones when they are loaded
+        let ruleset = equaio::rule::parse_ruleset_from_json(&rulestr).unwrap_or_else(|_| {
+            for err in validate::validate_ruleset(key, &rulestr) { warn!("{}", err); }
+            warn!("ruleset \"{}\" is invalid, \"{}\" is used instead", key, DEFAULT_RULESET);
+            self.ruleset(DEFAULT_RULESET)
+        });
+        self.rulesets.borrow_mut().insert(key.to_string(), ruleset.clone());
+        return ruleset;
+    }
+    pub fn ruleset(&self, name: &str) -> RuleSet {
+        self.cached_ruleset(name, || self.ruleset_source(name).to_string())
+    }
+    /// The ruleset of `ws_data` with only the rules that it allows.
+    /// `auto_rules` are the ids of the rules applied automatically, instead of the `auto` flags of the ruleset.
+    pub fn restricted_ruleset(&self, ws_data: &WorksheetData, auto_rules: Option<&[String]>) -> RuleSet {
+        let name = &ws_data.rule;
+        let is_unrestricted = ws_data.allowed_rules.is_empty() && ws_data.forbidden_rules.is_empty();
+        if is_unrestricted && auto_rules.is_none() { return self.ruleset(name); }
+        let mut key = format!("{}|{}|{}", name, ws_data.allowed_rules.join(","), ws_data.forbidden_rules.join(","));
+        if let Some(auto_rules) = auto_rules { key += &format!("|{}", auto_rules.join(",")); }
+        return self.cached_ruleset(&key, || {
+            let mut value: Value = serde_json::from_str(self.ruleset_source(name)).unwrap_or_default();
+            if let Some(rules) = value["rules"].as_array_mut() {
+                rules.retain(|rule| is_allowed_rule(ws_data, rule["id"].as_str().unwrap_or_default()));
+                for rule in rules.iter_mut().filter(|_| auto_rules.is_some()) {
+                    let id = rule["id"].as_str().unwrap_or_default().to_string();
+                    rule["auto"] = auto_rules.unwrap_or_default().contains(&id).into();
+                }
+            }
+            value.to_string()
+        });
+    }
+    /// The rules of the ruleset named `name`, in order
+    #[cfg(not(feature = "cli"))]
+    pub fn rules(&self, name: &str) -> Vec<RuleInfo> {
+        let value: Value = serde_json::from_str(self.ruleset_source(name)).unwrap_or_default();
+        let rules = value["rules"].as_array().cloned().unwrap_or_default();
+        return rules.iter().map(|rule| RuleInfo {
+            id: rule["id"].as_str().unwrap_or_default().to_string(),
+            label: rule["label"].as_str().unwrap_or_default().to_string(),
+            expr: rule["expr"].as_str().unwrap_or_default().to_string(),
+            variations: rule["variations"].as_array().map(|variations| variation_exprs(variations)),
+            auto: rule["auto"].as_bool().unwrap_or(false),
+        }).collect();
+    }
+    /// The variations applied to the rules of the ruleset named `name` that have none of their own
+    #[cfg(not(feature = "cli"))]
+    pub fn ruleset_variations(&self, name: &str) -> Vec<String> {
+        let value: Value = serde_json::from_str(self.ruleset_source(name)).unwrap_or_default();
+        return value["variations"].as_array().map(|variations| variation_exprs(variations)).unwrap_or_default();
+    }
+    /// The rules of the ruleset of `ws_data` that it allows
+    #[cfg(not(feature = "cli"))]
+    pub fn available_rules(&self, ws_data: &WorksheetData) -> Vec<RuleInfo> {
+        let mut rules = self.rules(&ws_data.rule);
+        rules.retain(|rule| is_allowed_rule(ws_data, &rule.id));
+        return rules;
+    }
+    pub fn presentation(&self, name: &str) -> Presentation {
+        if let Some(presentation) = self.presentations.borrow().get(name) { return presentation.clone(); }
+        // validated for bundled rulesets by the build script, and for the others when they are loaded
+        let presentation = Presentation::from_ruleset_json(self.ruleset_source(name)).unwrap_or_else(|err| {
+            warn!("ruleset \"{}\": {}", name, err);
+            Presentation::default()
+        });
+        self.presentations.borrow_mut().insert(name.to_string(), presentation.clone());
+        return presentation;
+    }
+}
+
+/// A rule of a ruleset json
+#[cfg(not(feature = "cli"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleInfo {
+    pub id: String,
+    pub label: String,
+    pub expr: String,
+    /// own variations of the rule, `None` if it uses the variations of the ruleset
+    pub variations: Option<Vec<String>>,
+    pub auto: bool,
+}
+
+#[cfg(not(feature = "cli"))]
+fn variation_exprs(variations: &[Value]) -> Vec<String> {
+    variations.iter().filter_map(|variation| variation["expr"].as_str()).map(|expr| expr.to_string()).collect()
+}
+
+fn is_allowed_rule(ws_data: &WorksheetData, rule_id: &str) -> bool {
+    let is_allowed = ws_data.allowed_rules.is_empty() || ws_data.allowed_rules.iter().any(|id| id == rule_id);
+    return is_allowed && !ws_data.forbidden_rules.iter().any(|id| id == rule_id);
+}
+
+/// The json of the bundled ruleset named `name` as written, before its `extends` is resolved
+fn builtin_ruleset_json(name: &str) -> Option<String> {
+    json::RULESETS.iter().find(|(n, _)| *n == name).map(|(_, rulestr)| rulestr.to_string())
+}
+
+/// The bundled rulesets with their `extends` resolved, the build script makes sure they resolve
+fn builtin_ruleset_sources() -> HashMap<String, String> {
+    return json::RULESETS.iter()
+        .map(|(name, rulestr)| (name.to_string(), extends::resolve_ruleset(name, &builtin_ruleset_json).unwrap_or(rulestr.to_string())))
+        .collect();
+}
+
+#[cfg(not(feature = "cli"))]
+pub type Content = Signal<ContentStore>;
+
+/// Provide the content store to the app, the local pack and rulesets are loaded from local storage
+#[cfg(not(feature = "cli"))]
+pub fn use_content_provider() -> Content {
+    let mut content = use_context_provider(|| Signal::new(ContentStore::builtin()));
+    use_hook(move || spawn(async move {
+        let stored = storage::load(LOCAL_PACK_STORAGE_KEY).await;
+        if let Some(local) = stored.and_then(|json| serde_json::from_str::<ContentPack>(&json).ok()) {
+            content.write().local = local;
+        }
+        let stored = storage::load(LOCAL_RULESETS_STORAGE_KEY).await;
+        if let Some(local_rulesets) = stored.and_then(|json| serde_json::from_str::<HashMap<String, String>>(&json).ok()) {
+            let mut store = content.write();
+            store.local_rulesets = local_rulesets;
+            store.resolve_local_rulesets();
+        }
+    }));
+    return content;
+}
+
+#[cfg(not(feature = "cli"))]
+pub fn use_content() -> Content {
+    return use_context::<Content>();
+}
@@ -0,0 +1,446 @@
+//! Courses: units of lessons of problems, taken in order.
+//! A unit or lesson is unlocked when its `prerequisites` are completed, by default the unit or lesson before it,
+//! and the problems of an unlocked lesson are unlocked one after the other as they are solved.
+use serde::{Deserialize, Serialize};
+// the progress and the course pages, left out of the cli
+#[cfg(not(feature = "cli"))]
+use std::collections::BTreeSet;
+#[cfg(not(feature = "cli"))]
+use dioxus::prelude::*;
+#[cfg(not(feature = "cli"))]
+use super::content::{self, ContentStore};
+#[cfg(not(feature = "cli"))]
+use super::storage;
+#[cfg(not(feature = "cli"))]
+use super::{Route, Router};
+
+#[cfg(not(feature = "cli"))]
+const PROGRESS_STORAGE_KEY: &str = "equaio-progress";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CourseData {
+    pub name: String,
+    pub units: Vec<UnitData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnitData {
+    pub id: String,
+    pub name: String,
+    /// ids of the units to complete first, the previous unit if not given
+    #[serde(default)]
+    pub prerequisites: Option<Vec<String>>,
+    pub lessons: Vec<LessonData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LessonData {
+    pub id: String,
+    pub name: String,
+    /// ids of the lessons of the course to complete first, the previous lesson of the unit if not given
+    #[serde(default)]
+    pub prerequisites: Option<Vec<String>>,
+    pub problem_ids: Vec<String>,
+}
+
+/// The problems solved by the user, persisted in local storage
+#[cfg(not(feature = "cli"))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Progress {
+    pub solved: BTreeSet<String>,
+}
+
+#[cfg(not(feature = "cli"))]
+impl Progress {
+    pub fn mark_solved(&mut self, problem_id: &str) {
+        if !self.solved.insert(problem_id.to_string()) { return; }
+        self.save();
+    }
+    pub fn reset(&mut self) {
+        self.solved.clear();
+        self.save();
+    }
+    fn save(&self) {
+        if let Ok(json) = serde_json::to_string(self) { storage::save(PROGRESS_STORAGE_KEY, &json); }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemState {
+    Locked,
+    Unlocked,
+    Completed,
+}
+
+#[cfg(not(feature = "cli"))]
+impl ItemState {
+    fn class(&self) -> &'static str {
+        match self {
+            ItemState::Locked => "course-item locked",
+            ItemState::Unlocked => "course-item",
+            ItemState::Completed => "course-item completed",
+        }
+    }
+}
+
+impl CourseData {
+    /// The problems, units and lessons referred to by the course that do not exist,
+    /// e.g. `course "Algebra", lesson "linear": unknown problem "linear_3"`
+    pub fn validate(&self, has_problem: impl Fn(&str) -> bool) -> Vec<String> {
+        let lessons = self.units.iter().flat_map(|unit| unit.lessons.iter()).collect::<Vec<_>>();
+        let mut errors = vec![];
+        for unit in &self.units {
+            for id in unit.prerequisites.iter().flatten().filter(|id| !self.units.iter().any(|unit| unit.id == **id)) {
+                errors.push(format!("course \"{}\", unit \"{}\": unknown prerequisite unit \"{}\"", self.name, unit.id, id));
+            }
+        }
+        for lesson in &lessons {
+            for id in lesson.prerequisites.iter().flatten().filter(|id| !lessons.iter().any(|lesson| lesson.id == **id)) {
+                errors.push(format!("course \"{}\", lesson \"{}\": unknown prerequisite lesson \"{}\"", self.name, lesson.id, id));
+            }
+            for id in lesson.problem_ids.iter().filter(|id| !has_problem(id)) {
+                errors.push(format!("course \"{}\", lesson \"{}\": unknown problem \"{}\"", self.name, lesson.id, id));
+            }
+        }
+        return errors;
+    }
+}
+
+/// The state of every unit, lesson and problem of a course
+#[cfg(not(feature = "cli"))]
+pub struct CourseStatus<'a> {
+    course: &'a CourseData,
+    /// ids of the completed problems
+    completed: BTreeSet<String>,
+}
+
+#[cfg(not(feature = "cli"))]
+impl<'a> CourseStatus<'a> {
+    /// Problems without a solution to check against are completed once they are unlocked,
+    /// which can unlock further problems, until no more are unlocked
+    pub fn new(course: &'a CourseData, content: &ContentStore, progress: &Progress) -> Self {
+        let mut status = CourseStatus { course, completed: progress.solved.clone() };
+        loop {
+            let unlocked_without_solution = status.problems()
+                .filter(|(_, _, _, id)| !status.completed.contains(*id))
+                .filter(|(_, _, _, id)| content.problem(id).is_some_and(|ws_data| ws_data.solution.is_empty()))
+                .filter(|(unit_index, lesson_index, problem_index, _)| status.is_problem_unlocked(*unit_index, *lesson_index, *problem_index))
+                .map(|(_, _, _, id)| id.clone())
+                .collect::<Vec<_>>();
+            if unlocked_without_solution.is_empty() { break; }
+            status.completed.extend(unlocked_without_solution);
+        }
+        return status;
+    }
+
+    /// `(unit_index, lesson_index, problem_index, problem_id)` of every problem of the course
+    fn problems(&self) -> impl Iterator<Item = (usize, usize, usize, &'a String)> {
+        self.course.units.iter().enumerate().flat_map(|(unit_index, unit)| {
+            unit.lessons.iter().enumerate().flat_map(move |(lesson_index, lesson)| {
+                lesson.problem_ids.iter().enumerate().map(move |(problem_index, id)| (unit_index, lesson_index, problem_index, id))
+            })
+        })
+    }
+    fn is_problem_completed(&self, problem_id: &str) -> bool {
+        return self.completed.contains(problem_id);
+    }
+    fn is_lesson_completed(&self, lesson: &LessonData) -> bool {
+        return lesson.problem_ids.iter().all(|id| self.is_problem_completed(id));
+    }
+    fn is_unit_completed(&self, unit: &UnitData) -> bool {
+        return unit.lessons.iter().all(|lesson| self.is_lesson_completed(lesson));
+    }
+    fn lesson(&self, lesson_id: &str) -> Option<&LessonData> {
+        return self.course.units.iter().flat_map(|unit| unit.lessons.iter()).find(|lesson| lesson.id == lesson_id);
+    }
+
+    fn is_unit_unlocked(&self, unit_index: usize) -> bool {
+        let unit = &self.course.units[unit_index];
+        return match &unit.prerequisites {
+            Some(ids) => ids.iter().all(|id| self.course.units.iter().find(|unit| unit.id == *id)
+                .is_some_and(|unit| self.is_unit_completed(unit))),
+            None => unit_index == 0 || self.is_unit_completed(&self.course.units[unit_index - 1]),
+        };
+    }
+    fn is_lesson_unlocked(&self, unit_index: usize, lesson_index: usize) -> bool {
+        if !self.is_unit_unlocked(unit_index) { return false; }
+        let lessons = &self.course.units[unit_index].lessons;
+        return match &lessons[lesson_index].prerequisites {
+            Some(ids) => ids.iter().all(|id| self.lesson(id).is_some_and(|lesson| self.is_lesson_completed(lesson))),
+            None => lesson_index == 0 || self.is_lesson_completed(&lessons[lesson_index - 1]),
+        };
+    }
+    fn is_problem_unlocked(&self, unit_index: usize, lesson_index: usize, problem_index: usize) -> bool {
+        let problem_ids = &self.course.units[unit_index].lessons[lesson_index].problem_ids;
+        return self.is_lesson_unlocked(unit_index, lesson_index)
+            && (problem_index == 0 || self.is_problem_completed(&problem_ids[problem_index - 1]));
+    }
+
+    pub fn unit_state(&self, unit_index: usize) -> ItemState {
+        if !self.is_unit_unlocked(unit_index) { return ItemState::Locked; }
+        if self.is_unit_completed(&self.course.units[unit_index]) { return ItemState::Completed; }
+        return ItemState::Unlocked;
+    }
+    pub fn lesson_state(&self, unit_index: usize, lesson_index: usize) -> ItemState {
+        if !self.is_lesson_unlocked(unit_index, lesson_index) { return ItemState::Locked; }
+        if self.is_lesson_completed(&self.course.units[unit_index].lessons[lesson_index]) { return ItemState::Completed; }
+        return ItemState::Unlocked;
+    }
+    pub fn problem_state(&self, unit_index: usize, lesson_index: usize, problem_index: usize) -> ItemState {
+        if !self.is_problem_unlocked(unit_index, lesson_index, problem_index) { return ItemState::Locked; }
+        let problem_id = &self.course.units[unit_index].lessons[lesson_index].problem_ids[problem_index];
+        if self.is_problem_completed(problem_id) { return ItemState::Completed; }
+        return ItemState::Unlocked;
+    }
+
+    /// Number of completed problems and number of problems of the course
+    pub fn completed_count(&self) -> (usize, usize) {
+        let completed = self.problems().filter(|(_, _, _, id)| self.is_problem_completed(id)).count();
+        return (completed, self.problems().count());
+    }
+}
+
+/// Provide the progress signal to the app, initialized from local storage
+#[cfg(not(feature = "cli"))]
+pub fn use_progress_provider() -> Signal<Progress> {
+    let mut progress = use_context_provider(|| Signal::new(Progress::default()));
+    use_hook(move || spawn(async move {
+        let stored = storage::load(PROGRESS_STORAGE_KEY).await;
+        if let Some(stored) = stored.and_then(|json| serde_json::from_str::<Progress>(&json).ok()) {
+            progress.set(stored);
+        }
+    }));
+    return progress;
+}
+
+/// Progress of the user, or an empty progress if no provider is mounted
+#[cfg(not(feature = "cli"))]
+pub fn use_progress() -> Signal<Progress> {
+    let fallback = use_signal(Progress::default);
+    return try_use_context::<Signal<Progress>>().unwrap_or(fallback);
+}
+
+/// The courses on the main menu with the number of solved problems
+#[cfg(not(feature = "cli"))]
+#[component]
+pub fn CourseList(router: Router) -> Element {
+    let content = content::use_content();
+    let progress = use_progress();
+    let content = content.read();
+    let progress = progress.read();
+    let courses = content.courses()
+        .map(|course| (course.name.clone(), CourseStatus::new(course, &content, &progress).completed_count()))
+        .collect::<Vec<_>>();
+    if courses.is_empty() { return rsx! {}; }
+    rsx! {
+        div {
+            class: "category-container",
+            div {
+                class: "category-header",
+                span { "Courses" }
+            }
+            for (name, (completed, total)) in courses {
+                div {
+                    class: "category-button",
+                    onclick: {
+                        let course_name = name.clone();
+                        move |_| { router.write().push(Route::Course { course_name: course_name.clone() }); }
+                    },
+                    span { "{name}" }
+                    span { class: "problem-meta", "{completed} / {total} solved" }
+                }
+            }
+        }
+    }
+}
+
+/// Overview of the units, lessons and problems of a course, with what is completed and what is still locked
+#[cfg(not(feature = "cli"))]
+#[component]
+pub fn CoursePage(router: Router, course_name: String) -> Element {
+    let content = content::use_content();
+    let mut progress = use_progress();
+    let content = content.read();
+    let Some(course) = content.course(&course_name).cloned() else {
+        return rsx! { div { "ERROR: course not found" } };
+    };
+    let current_progress = progress.read().clone();
+    let status = CourseStatus::new(&course, &content, &current_progress);
+    let (completed, total) = status.completed_count();
+    rsx! {
+        div {
+            class: "navbar",
+            div {
+                class: "navbar-left",
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().pop(); },
+                    "<"
+                }
+            }
+            div {
+                class: "navbar-center",
+                h2 { "{course.name}" }
+            }
+            div {
+                class: "navbar-right",
+                button {
+                    class: "navbar-button",
+                    title: "Forget the solved problems of every course",
+                    onclick: move |_| progress.write().reset(),
+                    "reset progress"
+                }
+            }
+        }
+        div {
+            class: "main-menu",
+            span { class: "course-summary", "{completed} of {total} problems solved" }
+            for (unit_index, unit) in course.units.iter().enumerate() {
+                div {
+                    class: "category-container",
+                    div {
+                        class: "category-header {status.unit_state(unit_index).class()}",
+                        span { "{unit.name}" }
+                    }
+                    for (lesson_index, lesson) in unit.lessons.iter().enumerate() {
+                        div {
+                            class: "course-lesson {status.lesson_state(unit_index, lesson_index).class()}",
+                            span { "{lesson.name}" }
+                            for (problem_index, problem_id) in lesson.problem_ids.iter().enumerate() {
+                                if let Some(ws_data) = content.problem(problem_id) {
+                                    CourseProblem {
+                                        router,
+                                        problem_id: problem_id.clone(),
+                                        label: ws_data.label.clone(),
+                                        state: status.problem_state(unit_index, lesson_index, problem_index)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+#[component]
+fn CourseProblem(router: Router, problem_id: String, label: String, state: ItemState) -> Element {
+    let mark = match state {
+        ItemState::Locked => "locked",
+        ItemState::Unlocked => "",
+        ItemState::Completed => "✓",
+    };
+    rsx! {
+        button {
+            class: "category-button {state.class()}",
+            disabled: state == ItemState::Locked,
+            onclick: move |_| { router.write().push(Route::ProblemPage { problem_id: problem_id.clone() }); },
+            span { "{label}" }
+            span { class: "problem-meta", "{mark}" }
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "cli")))]
+mod tests {
+    use super::*;
+    use crate::content::ContentPack;
+    use crate::worksheet::WorksheetData;
+
+    fn lesson(id: &str, problem_ids: &[&str]) -> LessonData {
+        LessonData { id: id.to_string(), name: id.to_string(), prerequisites: None, problem_ids: problem_ids.iter().map(|id| id.to_string()).collect() }
+    }
+    fn course(lessons: Vec<LessonData>) -> CourseData {
+        CourseData { name: "test".to_string(), units: vec![UnitData { id: "unit".to_string(), name: "unit".to_string(), prerequisites: None, lessons }] }
+    }
+    /// A store with the problems `ids`, those in `without_solution` have no solution
+    fn content(ids: &[&str], without_solution: &[&str]) -> ContentStore {
+        let problems = ids.iter().map(|id| {
+            let solution = if without_solution.contains(id) { vec![] } else { vec!["x = 1".to_string()] };
+            (id.to_string(), WorksheetData { solution, ..Default::default() })
+        }).collect();
+        let mut content = ContentStore::builtin();
+        content.add_pack(ContentPack { problems, ..Default::default() });
+        return content;
+    }
+    fn solved(ids: &[&str]) -> Progress {
+        Progress { solved: ids.iter().map(|id| id.to_string()).collect() }
+    }
+
+    #[test]
+    fn problems_unlock_in_order() {
+        let course = course(vec![lesson("a", &["p1", "p2"]), lesson("b", &["p3"])]);
+        let content = content(&["p1", "p2", "p3"], &[]);
+        let progress = solved(&["p1"]);
+        let status = CourseStatus::new(&course, &content, &progress);
+        assert_eq!(status.problem_state(0, 0, 0), ItemState::Completed);
+        assert_eq!(status.problem_state(0, 0, 1), ItemState::Unlocked);
+        assert_eq!(status.lesson_state(0, 0), ItemState::Unlocked);
+        assert_eq!(status.lesson_state(0, 1), ItemState::Locked);
+        assert_eq!(status.problem_state(0, 1, 0), ItemState::Locked);
+        assert_eq!(status.completed_count(), (1, 3));
+    }
+
+    #[test]
+    fn problems_without_solution_complete_once_unlocked() {
+        let course = course(vec![lesson("a", &["p1", "p2"]), lesson("b", &["p3", "p4"])]);
+        let content = content(&["p1", "p2", "p3", "p4"], &["p2", "p3"]);
+        let status = CourseStatus::new(&course, &content, &Progress::default());
+        assert_eq!(status.problem_state(0, 0, 1), ItemState::Locked);
+        assert_eq!(status.problem_state(0, 1, 0), ItemState::Locked);
+        assert_eq!(status.completed_count(), (0, 4));
+
+        let progress = solved(&["p1"]);
+        let status = CourseStatus::new(&course, &content, &progress);
+        assert_eq!(status.problem_state(0, 0, 1), ItemState::Completed);
+        assert_eq!(status.lesson_state(0, 0), ItemState::Completed);
+        assert_eq!(status.problem_state(0, 1, 0), ItemState::Completed);
+        assert_eq!(status.problem_state(0, 1, 1), ItemState::Unlocked);
+        assert_eq!(status.completed_count(), (3, 4));
+    }
+
+    #[test]
+    fn prerequisites_unlock_lessons() {
+        let mut lessons = vec![lesson("a", &["p1"]), lesson("b", &["p2"]), lesson("c", &["p3"])];
+        lessons[2].prerequisites = Some(vec!["a".to_string()]);
+        let course = course(lessons);
+        let content = content(&["p1", "p2", "p3"], &[]);
+        let progress = solved(&["p1"]);
+        let status = CourseStatus::new(&course, &content, &progress);
+        assert_eq!(status.lesson_state(0, 1), ItemState::Unlocked);
+        assert_eq!(status.lesson_state(0, 2), ItemState::Unlocked);
+    }
+
+    #[test]
+    fn cyclic_prerequisites_stay_locked() {
+        let mut lessons = vec![lesson("a", &["p1"]), lesson("b", &["p2"])];
+        lessons[0].prerequisites = Some(vec!["b".to_string()]);
+        lessons[1].prerequisites = Some(vec!["a".to_string()]);
+        let course = course(lessons);
+        let content = content(&["p1", "p2"], &["p1", "p2"]);
+        let status = CourseStatus::new(&course, &content, &Progress::default());
+        assert_eq!(status.lesson_state(0, 0), ItemState::Locked);
+        assert_eq!(status.lesson_state(0, 1), ItemState::Locked);
+    }
+
+    #[test]
+    fn validate_reports_unknown_ids() {
+        let mut lessons = vec![lesson("a", &["p1", "missing"]), lesson("b", &[])];
+        lessons[1].prerequisites = Some(vec!["nope".to_string()]);
+        let errors = course(lessons).validate(|id| id == "p1");
+        assert_eq!(errors, vec![
+            "course \"test\", lesson \"a\": unknown problem \"missing\"".to_string(),
+            "course \"test\", lesson \"b\": unknown prerequisite lesson \"nope\"".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn bundled_courses_are_valid() {
+        let content = ContentStore::builtin();
+        let errors = content.courses().flat_map(|course| course.validate(|id| content.problem(id).is_some())).collect::<Vec<_>>();
+        assert!(errors.is_empty(), "invalid bundled courses:\n{}", errors.join("\n"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Problem descriptions: Markdown with math between `$`, e.g. `Solve $(2 * x) - 1 = 3$ for $x$`.
+//! The math is parsed with the ruleset and variables of the problem and rendered like the worksheet lines,
+//! math that does not parse is shown as math text.
+use dioxus::prelude::*;
+use equaio::block::Block;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use super::content;
+use super::presentation::Presentation;
+use super::worksheet::{self, MathText, WorksheetData};
+
+#[derive(Debug, Clone, PartialEq)]
+enum ElementKind {
+    Paragraph,
+    Heading,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    List { ordered: bool },
+    Item,
+    BlockQuote,
+    CodeBlock,
+    Link { url: String },
+    /// tags without an element of their own, e.g. images, only their text is shown
+    Group,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Element(ElementKind, Vec<Node>),
+    Text(String),
+    Code(String),
+    Math { expr: String, display: bool },
+    LineBreak,
+}
+
+fn element_kind(tag: &Tag) -> ElementKind {
+    match tag {
+        Tag::Paragraph => ElementKind::Paragraph,
+        Tag::Heading { .. } => ElementKind::Heading,
+        Tag::Emphasis => ElementKind::Emphasis,
+        Tag::Strong => ElementKind::Strong,
+        Tag::Strikethrough => ElementKind::Strikethrough,
+        Tag::List(start) => ElementKind::List { ordered: start.is_some() },
+        Tag::Item => ElementKind::Item,
+        Tag::BlockQuote(_) => ElementKind::BlockQuote,
+        Tag::CodeBlock(_) => ElementKind::CodeBlock,
+        // descriptions come from opened content packs too, so no `javascript:` links
+        Tag::Link { dest_url, .. } if is_safe_url(dest_url) => ElementKind::Link { url: dest_url.to_string() },
+        _ => ElementKind::Group,
+    }
+}
+
+fn is_safe_url(url: &str) -> bool {
+    return ["http://", "https://", "mailto:"].iter().any(|scheme| url.starts_with(scheme)) || !url.contains(':');
+}
+
+fn parse_markdown(text: &str) -> Vec<Node> {
+    let mut stack = vec![(ElementKind::Group, Vec::<Node>::new())];
+    for event in Parser::new_ext(text, Options::ENABLE_MATH | Options::ENABLE_STRIKETHROUGH) {
+        let node = match event {
+            Event::Start(tag) => { stack.push((element_kind(&tag), vec![])); continue; },
+            Event::End(_) if stack.len() > 1 => {
+                let Some((kind, children)) = stack.pop() else { continue; };
+                Node::Element(kind, children)
+            },
+            Event::Text(text) => Node::Text(text.to_string()),
+            Event::Code(code) => Node::Code(code.to_string()),
+            Event::InlineMath(expr) => Node::Math { expr: expr.to_string(), display: false },
+            Event::DisplayMath(expr) => Node::Math { expr: expr.to_string(), display: true },
+            Event::SoftBreak => Node::Text(" ".to_string()),
+            Event::HardBreak => Node::LineBreak,
+            _ => continue,
+        };
+        if let Some((_, children)) = stack.last_mut() { children.push(node); }
+    }
+    return stack.into_iter().next().map(|(_, children)| children).unwrap_or_default();
+}
+
+/// The description of a problem rendered from its Markdown
+#[component]
+pub fn Description(ws_data: WorksheetData) -> Element {
+    worksheet::use_presentation_provider(&ws_data);
+    let nodes = parse_markdown(&ws_data.description.clone().unwrap_or_default());
+    rsx! {
+        div {
+            class: "description",
+            MarkdownNodes { nodes, ws_data }
+        }
+    }
+}
+
+#[component]
+fn MarkdownNodes(nodes: Vec<Node>, ws_data: WorksheetData) -> Element {
+    rsx! {
+        for node in nodes {
+            match node {
+                Node::Text(text) => rsx! { "{text}" },
+                Node::Code(code) => rsx! { code { "{code}" } },
+                Node::LineBreak => rsx! { br {} },
+                Node::Math { expr, display } => rsx! {
+                    InlineMath { expr, display, ws_data: ws_data.clone() }
+                },
+                Node::Element(kind, nodes) => {
+                    let ws_data = ws_data.clone();
+                    match kind {
+                        ElementKind::Paragraph => rsx! { p { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Heading => rsx! { h4 { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Emphasis => rsx! { em { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Strong => rsx! { strong { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Strikethrough => rsx! { del { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::List { ordered: true } => rsx! { ol { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::List { ordered: false } => rsx! { ul { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Item => rsx! { li { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::BlockQuote => rsx! { blockquote { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::CodeBlock => rsx! { pre { MarkdownNodes { nodes, ws_data } } },
+                        ElementKind::Link { url } => rsx! {
+                            a { href: "{url}", target: "_blank", MarkdownNodes { nodes, ws_data } }
+                        },
+                        ElementKind::Group => rsx! { MarkdownNodes { nodes, ws_data } },
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Math of a description, an expression of the ruleset of the problem if it parses
+#[component]
+fn InlineMath(expr: String, display: bool, ws_data: WorksheetData) -> Element {
+    let content = content::use_content();
+    let block_ctx = use_context::<Presentation>().block_context();
+    let block = worksheet::parse_expressions(&content.read(), &ws_data, &[expr.clone()]).first()
+        .map(|parsed| Block::from_root_expression(parsed, &block_ctx));
+    rsx! {
+        span {
+            class: if display { "description-math display" } else { "description-math" },
+            if let Some(block) = block {
+                worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+            } else {
+                MathText { text: expr, variables: ws_data.variables }
+            }
+        }
+    }
+}
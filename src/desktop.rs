@@ -0,0 +1,178 @@
+//! Native file handling of the desktop build. In the web build these components render nothing
+//! and content packs are downloaded.
+#[cfg(feature = "desktop")]
+pub use native::*;
+#[cfg(not(feature = "desktop"))]
+pub use stub::*;
+
+#[cfg(feature = "desktop")]
+mod native {
+    use std::path::{Path, PathBuf};
+    use dioxus::prelude::*;
+    use dioxus_logger::tracing::warn;
+    use crate::{Route, Router};
+    use crate::content::{Content, ContentPack};
+    use crate::session::{SessionData, Step};
+    use crate::worksheet::WorksheetData;
+    
+    const RECENT_FILES_LIMIT: usize = 10;
+    
+    enum OpenedFile {
+        Session(SessionData),
+        ContentPack(ContentPack),
+    }
+    
+    fn read_file(path: &Path) -> Option<OpenedFile> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        if let Ok(session) = serde_json::from_str::<SessionData>(&contents) { return Some(OpenedFile::Session(session)); }
+        if let Ok(pack) = serde_json::from_str::<ContentPack>(&contents) { return Some(OpenedFile::ContentPack(pack)); }
+        return None;
+    }
+    
+    fn recent_files_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("equaio").join("recent_files.json"))
+    }
+    fn load_recent_files() -> Vec<PathBuf> {
+        let contents = recent_files_path().and_then(|path| std::fs::read_to_string(path).ok());
+        return contents.and_then(|contents| serde_json::from_str(&contents).ok()).unwrap_or_default();
+    }
+    fn push_recent_file(file: &Path) {
+        let Some(path) = recent_files_path() else { return; };
+        let mut recent_files = load_recent_files();
+        recent_files.retain(|recent| recent != file);
+        recent_files.insert(0, file.to_path_buf());
+        recent_files.truncate(RECENT_FILES_LIMIT);
+        if let Some(dir) = path.parent() { let _ = std::fs::create_dir_all(dir); }
+        if let Ok(json) = serde_json::to_string_pretty(&recent_files) { let _ = std::fs::write(path, json); }
+    }
+    
+    /// Open a session as a problem page, or add a content pack to the main menu
+    fn open_file(path: &Path, mut router: Router, mut content: Content) {
+        match read_file(path) {
+            Some(OpenedFile::Session(session)) => router.write().push(Route::Session { session }),
+            Some(OpenedFile::ContentPack(pack)) => content.write().add_pack(pack),
+            None => { warn!("failed to open {}", path.display()); return; }
+        }
+        push_recent_file(path);
+    }
+    
+    #[component]
+    pub fn OpenFileButton(router: Router) -> Element {
+        let content = crate::content::use_content();
+        rsx! {
+            button {
+                class: "navbar-button",
+                onclick: move |_| {
+                    spawn(async move {
+                        let file = rfd::AsyncFileDialog::new()
+                            .add_filter("equaio session or content pack", &["json"])
+                            .pick_file().await;
+                        if let Some(file) = file { open_file(file.path(), router, content); }
+                    });
+                },
+                "open"
+            }
+        }
+    }
+    
+    #[component]
+    pub fn SaveSessionButton(problem_id: String, ws_data: WorksheetData, steps: Signal<Vec<Step>>) -> Element {
+        let problem_id = Some(problem_id).filter(|id| !id.is_empty());
+        rsx! {
+            button {
+                class: "navbar-button",
+                onclick: move |_| {
+                    let session = SessionData { problem_id: problem_id.clone(), ws_data: ws_data.clone(), steps: steps.read().clone() };
+                    spawn(async move {
+                        let file = rfd::AsyncFileDialog::new()
+                            .add_filter("equaio session", &["json"])
+                            .set_file_name("session.json")
+                            .save_file().await;
+                        let (Some(file), Ok(json)) = (file, serde_json::to_string_pretty(&session)) else { return; };
+                        match std::fs::write(file.path(), json) {
+                            Ok(_) => push_recent_file(file.path()),
+                            Err(err) => warn!("failed to save {}: {}", file.path().display(), err),
+                        }
+                    });
+                },
+                "save"
+            }
+        }
+    }
+    
+    /// Save the content pack to a file picked with a native dialog
+    pub fn save_content_pack(pack: &ContentPack) {
+        let Ok(json) = serde_json::to_string_pretty(pack) else { return; };
+        spawn(async move {
+            let file = rfd::AsyncFileDialog::new()
+                .add_filter("equaio content pack", &["json"])
+                .set_file_name("equaio-problems.json")
+                .save_file().await;
+            let Some(file) = file else { return; };
+            match std::fs::write(file.path(), json) {
+                Ok(_) => push_recent_file(file.path()),
+                Err(err) => warn!("failed to save {}: {}", file.path().display(), err),
+            }
+        });
+    }
+    
+    #[component]
+    pub fn RecentFiles(router: Router) -> Element {
+        let content = crate::content::use_content();
+        let recent_files = load_recent_files();
+        if recent_files.is_empty() { return rsx! {}; }
+        rsx! {
+            div {
+                class: "category-container",
+                div {
+                    class: "category-header",
+                    span { "Recent files" }
+                }
+                for path in recent_files {
+                    div {
+                        class: "category-button",
+                        onclick: {
+                            let path = path.clone();
+                            move |_| open_file(&path, router, content)
+                        },
+                        span { "{path.file_name().unwrap_or_default().to_string_lossy()}" }
+                        span { class: "recent-file-path", "{path.display()}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "desktop"))]
+mod stub {
+    use dioxus::prelude::*;
+    use crate::Router;
+    use crate::content::ContentPack;
+    use crate::session::Step;
+    use crate::worksheet::WorksheetData;
+    
+    #[component]
+    pub fn OpenFileButton(router: Router) -> Element {
+        let _ = router;
+        rsx! {}
+    }
+    
+    #[component]
+    pub fn SaveSessionButton(problem_id: String, ws_data: WorksheetData, steps: Signal<Vec<Step>>) -> Element {
+        let _ = (problem_id, ws_data, steps);
+        rsx! {}
+    }
+    
+    /// Download the content pack
+    pub fn save_content_pack(pack: &ContentPack) {
+        let Ok(json) = serde_json::to_string_pretty(pack) else { return; };
+        crate::storage::download("equaio-problems.json", &json);
+    }
+    
+    #[component]
+    pub fn RecentFiles(router: Router) -> Element {
+        let _ = router;
+        rsx! {}
+    }
+}
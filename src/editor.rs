@@ -0,0 +1,209 @@
+use dioxus::prelude::*;
+use equaio::block::Block;
+use super::content;
+use super::desktop;
+use super::session::SessionData;
+use super::worksheet::{self, Difficulty, WorksheetData};
+use super::{Route, Router};
+
+fn split_list(text: &str, separator: char) -> Vec<String> {
+    text.split(separator).map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// Problems found in the form, empty if the problem can be saved
+fn validate(content: &content::ContentStore, problem_id: &str, ws_data: &WorksheetData) -> Vec<String> {
+    let mut errors = vec![];
+    if problem_id.is_empty() || problem_id.contains(char::is_whitespace) {
+        errors.push("the id must be non-empty and without spaces".to_string());
+    } else if content.is_readonly_problem(problem_id) {
+        errors.push(format!("the id \"{}\" is already used by a bundled problem", problem_id));
+    }
+    if ws_data.label.trim().is_empty() { errors.push("the label is empty".to_string()); }
+    if ws_data.initial_expressions.is_empty() { errors.push("there is no initial expression".to_string()); }
+    let rule_ids = content.rules(&ws_data.rule).into_iter().map(|rule| rule.id).collect::<Vec<_>>();
+    for id in ws_data.allowed_rules.iter().chain(ws_data.forbidden_rules.iter()) {
+        if !rule_ids.contains(id) { errors.push(format!("there is no rule \"{}\" in the ruleset", id)); }
+    }
+    for (name, exprs) in [("initial expression", &ws_data.initial_expressions), ("solution", &ws_data.solution)] {
+        for expr in exprs {
+            if worksheet::parse_expressions(content, ws_data, &[expr.clone()]).is_empty() {
+                errors.push(format!("cannot parse the {} \"{}\"", name, expr));
+            }
+        }
+    }
+    return errors;
+}
+
+#[component]
+pub fn ProblemEditorPage(router: Router, problem_id: Option<String>, draft: Option<WorksheetData>) -> Element {
+    let mut content = content::use_content();
+    let initial = draft.or_else(|| problem_id.as_ref().and_then(|id| content.peek().problem(id).cloned()));
+    let mut id = use_signal(|| problem_id.clone().unwrap_or_default());
+    let mut label = use_signal(|| initial.as_ref().map(|ws| ws.label.clone()).unwrap_or_default());
+    let mut sublabel = use_signal(|| initial.as_ref().and_then(|ws| ws.sublabel.clone()).unwrap_or_default());
+    let mut description = use_signal(|| initial.as_ref().and_then(|ws| ws.description.clone()).unwrap_or_default());
+    let mut rule = use_signal(|| initial.as_ref().map(|ws| ws.rule.clone()).unwrap_or("algebra".to_string()));
+    let mut variables = use_signal(|| initial.as_ref().map(|ws| ws.variables.join(", ")).unwrap_or_default());
+    let mut initial_expressions = use_signal(|| initial.as_ref().map(|ws| ws.initial_expressions.join("\n")).unwrap_or_default());
+    let mut solution = use_signal(|| initial.as_ref().map(|ws| ws.solution.join("\n")).unwrap_or_default());
+    let mut allowed_rules = use_signal(|| initial.as_ref().map(|ws| ws.allowed_rules.join(", ")).unwrap_or_default());
+    let mut forbidden_rules = use_signal(|| initial.as_ref().map(|ws| ws.forbidden_rules.join(", ")).unwrap_or_default());
+    let mut difficulty = use_signal(|| initial.as_ref().and_then(|ws| ws.difficulty));
+    let mut tags = use_signal(|| initial.as_ref().map(|ws| ws.tags.join(", ")).unwrap_or_default());
+    let mut estimated_minutes = use_signal(|| initial.as_ref().and_then(|ws| ws.estimated_minutes).map(|m| m.to_string()).unwrap_or_default());
+    let mut is_saved = use_signal(|| false);
+
+    let ws_data = use_memo(move || {
+        let sublabel_str = sublabel.read().trim().to_string();
+        WorksheetData {
+            label: label.read().clone(),
+            sublabel: if sublabel_str.is_empty() { None } else { Some(sublabel_str) },
+            description: Some(description.read().clone()).filter(|text| !text.trim().is_empty()),
+            rule: rule.read().clone(),
+            variables: split_list(&variables.read(), ','),
+            initial_expressions: split_list(&initial_expressions.read(), '\n'),
+            solution: split_list(&solution.read(), '\n'),
+            allowed_rules: split_list(&allowed_rules.read(), ','),
+            forbidden_rules: split_list(&forbidden_rules.read(), ','),
+            difficulty: *difficulty.read(),
+            tags: split_list(&tags.read(), ','),
+            estimated_minutes: estimated_minutes.read().trim().parse().ok(),
+        }
+    });
+    // parses every expression, so only rerun when the problem or the content changes
+    let errors = use_memo(move || validate(&content.read(), &id.read(), &ws_data.read()));
+    let ws_data = ws_data.read().clone();
+    let errors = errors.read().clone();
+    let has_errors = !errors.is_empty();
+    let ruleset_names = content.read().ruleset_names();
+    let preview_key = format!("{}:{}", ws_data.rule, ws_data.variables.join(","));
+
+    rsx! {
+        div {
+            class: "navbar",
+            div {
+                class: "navbar-left",
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().pop(); },
+                    "<"
+                }
+            }
+            div {
+                class: "navbar-right",
+                button {
+                    class: "navbar-button",
+                    onclick: {
+                        let ws_data = ws_data.clone();
+                        move |_| {
+                            // keep the form when coming back from the test play
+                            let problem_id = Some(id.peek().clone()).filter(|id| !id.is_empty());
+                            let mut router = router.write();
+                            router.pop();
+                            router.push(Route::ProblemEditor { problem_id, draft: Some(ws_data.clone()) });
+                            router.push(Route::Session { session: SessionData { problem_id: None, ws_data: ws_data.clone(), steps: vec![] } });
+                        }
+                    },
+                    "test play"
+                }
+                button {
+                    class: "navbar-button",
+                    disabled: has_errors,
+                    onclick: {
+                        let ws_data = ws_data.clone();
+                        move |_| {
+                            content.write().save_local_problem(id.peek().clone(), ws_data.clone());
+                            is_saved.set(true);
+                        }
+                    },
+                    if *is_saved.read() { "saved" } else { "save" }
+                }
+                ExportLocalPackButton {}
+            }
+        }
+        div {
+            class: "editor",
+            div {
+                class: "editor-form",
+                label { "id" }
+                input { value: "{id}", oninput: move |e| { id.set(e.value()); is_saved.set(false); } }
+                label { "label" }
+                input { value: "{label}", oninput: move |e| { label.set(e.value()); is_saved.set(false); } }
+                label { "sublabel" }
+                input { value: "{sublabel}", oninput: move |e| { sublabel.set(e.value()); is_saved.set(false); } }
+                label { "description (Markdown, math between $)" }
+                textarea { rows: 4, value: "{description}", oninput: move |e| { description.set(e.value()); is_saved.set(false); } }
+                label { "ruleset" }
+                select {
+                    value: "{rule}",
+                    onchange: move |e| { rule.set(e.value()); is_saved.set(false); },
+                    for name in ruleset_names {
+                        option { value: "{name}", selected: name == *rule.read(), "{name}" }
+                    }
+                }
+                label { "variables (comma separated)" }
+                input { value: "{variables}", oninput: move |e| { variables.set(e.value()); is_saved.set(false); } }
+                label { "initial expressions (one per line)" }
+                textarea { rows: 4, value: "{initial_expressions}", oninput: move |e| { initial_expressions.set(e.value()); is_saved.set(false); } }
+                label { "solution (one per line)" }
+                textarea { rows: 2, value: "{solution}", oninput: move |e| { solution.set(e.value()); is_saved.set(false); } }
+                label { "allowed rules (comma separated ids, all if empty)" }
+                input { value: "{allowed_rules}", oninput: move |e| { allowed_rules.set(e.value()); is_saved.set(false); } }
+                label { "forbidden rules (comma separated ids)" }
+                input { value: "{forbidden_rules}", oninput: move |e| { forbidden_rules.set(e.value()); is_saved.set(false); } }
+                label { "difficulty" }
+                select {
+                    onchange: move |e| { difficulty.set(Difficulty::from_name(&e.value())); is_saved.set(false); },
+                    option { value: "", selected: difficulty.read().is_none(), "unspecified" }
+                    for level in Difficulty::ALL {
+                        option { value: level.name(), selected: Some(level) == *difficulty.read(), "{level.name()}" }
+                    }
+                }
+                label { "tags (comma separated)" }
+                input { value: "{tags}", oninput: move |e| { tags.set(e.value()); is_saved.set(false); } }
+                label { "estimated time (minutes)" }
+                input { r#type: "number", min: 1, value: "{estimated_minutes}", oninput: move |e| { estimated_minutes.set(e.value()); is_saved.set(false); } }
+            }
+            if has_errors {
+                ul {
+                    class: "editor-errors",
+                    for err in errors {
+                        li { "{err}" }
+                    }
+                }
+            }
+            // remounted when the presentation changes
+            ExpressionPreview { key: "{preview_key}", ws_data }
+        }
+    }
+}
+
+#[component]
+fn ExpressionPreview(ws_data: WorksheetData) -> Element {
+    let content = content::use_content();
+    let block_ctx = worksheet::use_presentation_provider(&ws_data).block_context();
+    let blocks = worksheet::parse_expressions(&content.read(), &ws_data, &ws_data.initial_expressions).iter()
+        .map(|expr| Block::from_root_expression(expr, &block_ctx))
+        .collect::<Vec<_>>();
+    rsx! {
+        div {
+            class: "editor-preview",
+            for block in blocks {
+                worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+            }
+        }
+    }
+}
+
+/// Save the problems made in the editor as a content pack, with a native dialog in the desktop build
+#[component]
+fn ExportLocalPackButton() -> Element {
+    let content = content::use_content();
+    rsx! {
+        button {
+            class: "navbar-button",
+            onclick: move |_| desktop::save_content_pack(content.read().local_pack()),
+            "export"
+        }
+    }
+}
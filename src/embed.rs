@@ -0,0 +1,99 @@
+//! Single worksheet mounted without the navbar, for embedding in other pages.
+//!
+//! The problem is given either in the query, `?embed=<problem_id>` or `?embed&data=<worksheet json>`,
+//! or as data attributes of the mount element, `data-embed-problem="<problem_id>"` or `data-embed-worksheet="<worksheet json>"`.
+//! Events are posted to the host page with `postMessage`, see [`EmbedEvent`].
+use std::cell::Cell;
+use std::rc::Rc;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use super::content;
+use super::session::Step;
+use super::worksheet::{self, WorksheetData};
+
+#[derive(Deserialize, Debug, Default)]
+struct EmbedConfig {
+    embed: bool,
+    problem_id: Option<String>,
+    worksheet: Option<String>,
+}
+
+/// The embedded problem, if the app is mounted as an embed
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbedTarget {
+    pub problem_id: String,
+    pub ws_data: Option<WorksheetData>,
+}
+
+pub async fn read_embed_target() -> Option<EmbedTarget> {
+    let config = eval(r#"
+        const params = new URLSearchParams(window.location.search);
+        const dataset = document.getElementById("main")?.dataset ?? {};
+        return {
+            embed: params.has("embed") || dataset.embedProblem !== undefined || dataset.embedWorksheet !== undefined,
+            problem_id: params.get("embed") || dataset.embedProblem || null,
+            worksheet: params.get("data") || dataset.embedWorksheet || null,
+        };
+    "#).join().await.ok()?;
+    let config: EmbedConfig = serde_json::from_value(config).unwrap_or_default();
+    if !config.embed { return None; }
+    let ws_data = config.worksheet.and_then(|json| serde_json::from_str(&json).ok());
+    return Some(EmbedTarget { problem_id: config.problem_id.unwrap_or_default(), ws_data });
+}
+
+/// Messages posted to the host page, tagged with `"source": "equaio"`
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EmbedEvent {
+    Ready { problem_id: String },
+    StepApplied { problem_id: String, step: Step, step_count: usize },
+    Solved { problem_id: String, step_count: usize },
+}
+
+fn post_message(event: &EmbedEvent) {
+    let Ok(mut payload) = serde_json::to_value(event) else { return; };
+    payload["source"] = "equaio".into();
+    let _ = eval(&format!("window.parent.postMessage({}, '*');", payload));
+}
+
+#[component]
+pub fn EmbedPage(target: EmbedTarget) -> Element {
+    let content = content::use_content();
+    let EmbedTarget { problem_id, ws_data } = target;
+    let ws_data = ws_data.or_else(|| content.read().problem(&problem_id).cloned());
+    let steps = use_signal(Vec::<Step>::new);
+    
+    use_hook({
+        let problem_id = problem_id.clone();
+        move || post_message(&EmbedEvent::Ready { problem_id })
+    });
+    let last_steps_len = use_hook(|| Rc::new(Cell::new(0)));
+    use_effect({
+        let problem_id = problem_id.clone();
+        move || {
+            let steps = steps.read();
+            if let Some(step) = steps.last().filter(|_| steps.len() > last_steps_len.get()) {
+                post_message(&EmbedEvent::StepApplied { problem_id: problem_id.clone(), step: step.clone(), step_count: steps.len() });
+            }
+            last_steps_len.set(steps.len());
+        }
+    });
+    
+    rsx! {
+        div {
+            class: "embed",
+            if let Some(ws_data) = ws_data {
+                worksheet::Worksheet {
+                    ws_data, steps,
+                    on_solved: move |_| post_message(&EmbedEvent::Solved { 
+                        problem_id: problem_id.clone(), step_count: steps.peek().len() 
+                    })
+                }
+            } else {
+                div {
+                    "ERROR: problem not found"
+                }
+            }
+        }
+    }
+}
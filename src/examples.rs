@@ -0,0 +1,105 @@
+//! The `examples` of the rules of a ruleset, e.g.
+//! `{"expr": "x + 0 = 2", "variables": ["x"], "select": [1], "expected": ["x = 2"]}`,
+//! where `select` are symbol indices of `expr` and `expected` are the results of applying the rule there.
+use serde::Deserialize;
+use serde_json::Value;
+use super::content::ContentStore;
+use super::presentation::block_to_text;
+use super::session;
+use super::worksheet::{self, WorksheetData};
+use equaio::block::Block;
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuleExample {
+    expr: String,
+    #[serde(default)]
+    variables: Vec<String>,
+    select: Vec<usize>,
+    expected: Vec<String>,
+}
+
+/// Results of applying the rule labelled `label` on `example`, as text
+fn apply_example(content: &ContentStore, ruleset_name: &str, label: &str, example: &RuleExample) -> Result<(Vec<String>, Vec<String>), String> {
+    let ws_data = WorksheetData {
+        rule: ruleset_name.to_string(),
+        variables: example.variables.clone(),
+        initial_expressions: vec![example.expr.clone()],
+        ..Default::default()
+    };
+    let block_ctx = worksheet::get_presentation(content, &ws_data).block_context();
+    let to_text = |expr: &equaio::expression::Expression| block_to_text(&Block::from_root_expression(expr, &block_ctx));
+
+    let ws = worksheet::init_worksheet(content, ws_data.clone());
+    let seq = ws.get(0).ok_or(format!("cannot parse \"{}\"", example.expr))?;
+    let addresses = session::symbol_indices_to_addresses(&seq, &block_ctx, &example.select)
+        .ok_or(format!("selected symbols {:?} are out of range", example.select))?;
+    let mut results = seq.get_possible_actions(&addresses).iter()
+        .filter(|(action, _)| action.to_string() == label)
+        .map(|(_, expr)| to_text(expr))
+        .collect::<Vec<_>>();
+    let expected_exprs = worksheet::parse_expressions(content, &ws_data, &example.expected);
+    if expected_exprs.len() != example.expected.len() { return Err(format!("cannot parse the expected {:?}", example.expected)); }
+    let mut expected = expected_exprs.iter().map(to_text).collect::<Vec<_>>();
+    for texts in [&mut results, &mut expected] {
+        texts.sort();
+        texts.dedup();
+    }
+    return Ok((expected, results));
+}
+
+/// Every rule example of the ruleset named `ruleset_name` that does not give the expected results
+pub fn check_rule_examples(content: &ContentStore, ruleset_name: &str) -> Vec<String> {
+    let value: Value = serde_json::from_str(content.ruleset_source(ruleset_name)).unwrap_or_default();
+    let rules = value["rules"].as_array().cloned().unwrap_or_default();
+    let mut errors = vec![];
+    for rule in rules {
+        // automatic rules are applied by the worksheet on its own, not offered as actions
+        if rule["auto"].as_bool().unwrap_or(false) { continue; }
+        let id = rule["id"].as_str().unwrap_or_default();
+        let label = rule["label"].as_str().unwrap_or_default();
+        let examples: Vec<RuleExample> = match serde_json::from_value(rule["examples"].clone()) {
+            Ok(examples) => examples,
+            Err(_) if rule["examples"].is_null() => vec![],
+            Err(err) => { errors.push(format!("ruleset \"{}\", rule \"{}\": invalid examples: {}", ruleset_name, id, err)); continue; }
+        };
+        for (i, example) in examples.iter().enumerate() {
+            let prefix = format!("ruleset \"{}\", rule \"{}\", example {}", ruleset_name, id, i + 1);
+            match apply_example(content, ruleset_name, label, example) {
+                Ok((expected, results)) if expected != results => {
+                    errors.push(format!("{}: expected {:?}, got {:?}", prefix, expected, results));
+                },
+                Ok(_) => {},
+                Err(err) => errors.push(format!("{}: {}", prefix, err)),
+            }
+        }
+    }
+    return errors;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn bundled_rule_examples() {
+        let content = ContentStore::builtin();
+        let errors = json::RULESETS.iter()
+            .flat_map(|(name, _)| check_rule_examples(&content, name))
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "failing rule examples:\n{}", errors.join("\n"));
+    }
+
+    #[test]
+    fn bundled_rules_have_examples() {
+        let content = ContentStore::builtin();
+        let missing = json::RULESETS.iter().flat_map(|(name, _)| {
+            let value: Value = serde_json::from_str(content.ruleset_source(name)).unwrap_or_default();
+            value["rules"].as_array().cloned().unwrap_or_default().into_iter()
+                .filter(|rule| !rule["auto"].as_bool().unwrap_or(false))
+                .filter(|rule| rule["examples"].as_array().is_none_or(|examples| examples.is_empty()))
+                .map(move |rule| format!("ruleset \"{}\", rule \"{}\"", name, rule["id"].as_str().unwrap_or_default()))
+        }).collect::<Vec<_>>();
+        assert!(missing.is_empty(), "rules without examples:\n{}", missing.join("\n"));
+    }
+}
@@ -0,0 +1,128 @@
+//! Rulesets that are written as a diff of another ruleset, e.g.
+//! `{"extends": "algebra", "override_rules": {"add_zero": {"auto": true}}, "remove_rules": ["add_self"], "rules": [...]}`.
+//! Shared with the build script, which validates the resolved bundled rulesets.
+use serde_json::{Map, Value};
+
+const MAX_DEPTH: usize = 16;
+
+fn rule_id(rule: &Value) -> Option<&str> {
+    rule.get("id").and_then(|id| id.as_str())
+}
+
+fn resolve_value(name: &str, lookup: &dyn Fn(&str) -> Option<String>, depth: usize) -> Result<Value, String> {
+    if depth > MAX_DEPTH { return Err(format!("ruleset \"{}\": too deeply nested or cyclic \"extends\"", name)); }
+    let rulestr = lookup(name).ok_or(format!("unknown ruleset \"{}\"", name))?;
+    let value: Value = serde_json::from_str(&rulestr).map_err(|err| format!("ruleset \"{}\": invalid json: {}", name, err))?;
+    let Some(base_name) = value.get("extends").and_then(|base| base.as_str()) else { return Ok(value); };
+    let mut resolved = resolve_value(base_name, lookup, depth + 1)?;
+    let Value::Object(fields) = &value else { return Err(format!("ruleset \"{}\": expected an object", name)); };
+
+    let mut rules = resolved["rules"].as_array().cloned().unwrap_or_default();
+    let unknown_rule = |id: &str| format!("ruleset \"{}\": rule \"{}\" is not in \"{}\"", name, id, base_name);
+    for id in fields.get("remove_rules").and_then(|ids| ids.as_array()).into_iter().flatten() {
+        let id = id.as_str().unwrap_or_default();
+        if !rules.iter().any(|rule| rule_id(rule) == Some(id)) { return Err(unknown_rule(id)); }
+        rules.retain(|rule| rule_id(rule) != Some(id));
+    }
+    let empty = Map::new();
+    let overrides = fields.get("override_rules").and_then(|overrides| overrides.as_object()).unwrap_or(&empty);
+    for (id, override_fields) in overrides {
+        let rule = rules.iter_mut().find(|rule| rule_id(rule) == Some(id)).ok_or(unknown_rule(id))?;
+        for (field, value) in override_fields.as_object().into_iter().flatten() {
+            rule[field] = value.clone();
+        }
+    }
+    // added rules replace the inherited rule with the same id
+    for rule in fields.get("rules").and_then(|rules| rules.as_array()).into_iter().flatten() {
+        match rules.iter_mut().find(|inherited| rule_id(inherited).is_some() && rule_id(inherited) == rule_id(rule)) {
+            Some(inherited) => *inherited = rule.clone(),
+            None => rules.push(rule.clone()),
+        }
+    }
+
+    for (field, value) in fields {
+        if ["extends", "override_rules", "remove_rules", "rules"].contains(&field.as_str()) { continue; }
+        resolved[field] = value.clone();
+    }
+    resolved["rules"] = Value::Array(rules);
+    return Ok(resolved);
+}
+
+/// The full json of the ruleset named `name`, with its `extends` chain applied and its name set to `name`
+pub fn resolve_ruleset(name: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut resolved = resolve_value(name, lookup, 0)?;
+    resolved["name"] = name.into();
+    return serde_json::to_string_pretty(&resolved).map_err(|err| err.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolve(name: &str, rulesets: &[(&str, Value)]) -> Result<Value, String> {
+        let lookup = |name: &str| rulesets.iter().find(|(n, _)| *n == name).map(|(_, value)| value.to_string());
+        return resolve_ruleset(name, &lookup).map(|rulestr| serde_json::from_str(&rulestr).unwrap());
+    }
+
+    fn base() -> (&'static str, Value) {
+        ("base", json!({
+            "name": "base",
+            "variations": [{ "expr": "A + B = B + A" }],
+            "rules": [
+                { "id": "add_zero", "expr": "X + 0 = X", "label": "Addition with 0" },
+                { "id": "mul_one", "expr": "X * 1 = X", "label": "Multiplication by 1" }
+            ]
+        }))
+    }
+
+    #[test]
+    fn overrides_fields_of_inherited_rules() {
+        let child = json!({ "extends": "base", "override_rules": { "add_zero": { "auto": true } } });
+        let resolved = resolve("child", &[base(), ("child", child)]).unwrap();
+        assert_eq!(resolved["rules"][0], json!({ "id": "add_zero", "expr": "X + 0 = X", "label": "Addition with 0", "auto": true }));
+        assert_eq!(resolved["rules"][1], base().1["rules"][1]);
+        assert_eq!(resolved["variations"], base().1["variations"]);
+    }
+
+    #[test]
+    fn removes_rules() {
+        let child = json!({ "extends": "base", "remove_rules": ["add_zero"] });
+        let resolved = resolve("child", &[base(), ("child", child)]).unwrap();
+        assert_eq!(resolved["rules"], json!([base().1["rules"][1]]));
+    }
+
+    #[test]
+    fn added_rules_replace_inherited_rules_with_the_same_id() {
+        let replaced = json!({ "id": "add_zero", "expr": "0 + X = X", "label": "Addition to 0" });
+        let added = json!({ "id": "sub_zero", "expr": "X - 0 = X", "label": "Subtraction of 0" });
+        let child = json!({ "extends": "base", "rules": [replaced, added] });
+        let resolved = resolve("child", &[base(), ("child", child)]).unwrap();
+        assert_eq!(resolved["rules"], json!([replaced, base().1["rules"][1], added]));
+    }
+
+    #[test]
+    fn reports_unknown_rule_ids() {
+        let child = json!({ "extends": "base", "remove_rules": ["pow_one"] });
+        assert_eq!(resolve("child", &[base(), ("child", child)]), Err("ruleset \"child\": rule \"pow_one\" is not in \"base\"".to_string()));
+        let child = json!({ "extends": "base", "override_rules": { "pow_one": { "auto": true } } });
+        assert_eq!(resolve("child", &[base(), ("child", child)]), Err("ruleset \"child\": rule \"pow_one\" is not in \"base\"".to_string()));
+        assert_eq!(resolve("child", &[base()]), Err("unknown ruleset \"child\"".to_string()));
+    }
+
+    #[test]
+    fn reports_cyclic_extends() {
+        let a = json!({ "extends": "b", "rules": [] });
+        let b = json!({ "extends": "a", "rules": [] });
+        let err = resolve("a", &[("a", a), ("b", b)]).unwrap_err();
+        assert!(err.ends_with("too deeply nested or cyclic \"extends\""), "{}", err);
+    }
+
+    #[test]
+    fn names_the_resolved_ruleset_after_the_child() {
+        let child = json!({ "extends": "base", "rules": [] });
+        let resolved = resolve("child", &[base(), ("child", child)]).unwrap();
+        assert_eq!(resolved["name"], "child");
+        assert_eq!(resolve("base", &[base()]).unwrap()["name"], "base");
+    }
+}
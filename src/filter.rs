@@ -0,0 +1,107 @@
+//! Filtering, sorting and searching the problems of the main menu
+use dioxus::prelude::*;
+use super::worksheet::{Difficulty, WorksheetData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SortOrder {
+    /// the order of the category
+    #[default]
+    Category,
+    Label,
+    Difficulty,
+    Time,
+}
+
+impl SortOrder {
+    const ALL: [SortOrder; 4] = [SortOrder::Category, SortOrder::Label, SortOrder::Difficulty, SortOrder::Time];
+    fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Category => "category order",
+            SortOrder::Label => "label",
+            SortOrder::Difficulty => "difficulty",
+            SortOrder::Time => "estimated time",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProblemFilter {
+    /// searched in the label, sublabel and initial expressions
+    pub search: String,
+    pub difficulty: Option<Difficulty>,
+    pub tag: Option<String>,
+    pub ruleset: Option<String>,
+    pub sort: SortOrder,
+}
+
+impl ProblemFilter {
+    pub fn matches(&self, ws_data: &WorksheetData) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let texts = [&ws_data.label].into_iter()
+            .chain(ws_data.sublabel.iter())
+            .chain(ws_data.initial_expressions.iter());
+        let matches_search = search.is_empty() || texts.into_iter().any(|text| text.to_lowercase().contains(&search));
+        let matches_difficulty = self.difficulty.is_none() || self.difficulty == ws_data.difficulty;
+        let matches_tag = self.tag.as_ref().is_none_or(|tag| ws_data.tags.contains(tag));
+        let matches_ruleset = self.ruleset.as_ref().is_none_or(|ruleset| *ruleset == ws_data.rule);
+        return matches_search && matches_difficulty && matches_tag && matches_ruleset;
+    }
+    /// The problems of `problems` that match, in the selected order
+    pub fn apply(&self, problems: Vec<(String, WorksheetData)>) -> Vec<(String, WorksheetData)> {
+        let mut problems = problems.into_iter().filter(|(_, ws_data)| self.matches(ws_data)).collect::<Vec<_>>();
+        match self.sort {
+            SortOrder::Category => {},
+            SortOrder::Label => problems.sort_by(|(_, a), (_, b)| a.label.cmp(&b.label)),
+            // problems without difficulty or time come last
+            SortOrder::Difficulty => problems.sort_by_key(|(_, ws_data)| (ws_data.difficulty.is_none(), ws_data.difficulty)),
+            SortOrder::Time => problems.sort_by_key(|(_, ws_data)| (ws_data.estimated_minutes.is_none(), ws_data.estimated_minutes)),
+        }
+        return problems;
+    }
+}
+
+#[component]
+pub fn FilterBar(filter: Signal<ProblemFilter>, tags: Vec<String>, rulesets: Vec<String>) -> Element {
+    let current = filter.read().clone();
+    rsx! {
+        div {
+            class: "filter-bar",
+            input {
+                class: "filter-search",
+                placeholder: "search problems",
+                value: "{current.search}",
+                oninput: move |e| filter.write().search = e.value()
+            }
+            select {
+                onchange: move |e| filter.write().difficulty = Difficulty::from_name(&e.value()),
+                option { value: "", "any difficulty" }
+                for difficulty in Difficulty::ALL {
+                    option { value: difficulty.name(), selected: current.difficulty == Some(difficulty), "{difficulty.name()}" }
+                }
+            }
+            select {
+                onchange: move |e| filter.write().tag = Some(e.value()).filter(|tag| !tag.is_empty()),
+                option { value: "", "any tag" }
+                for tag in tags {
+                    option { value: "{tag}", selected: current.tag.as_ref() == Some(&tag), "{tag}" }
+                }
+            }
+            select {
+                onchange: move |e| filter.write().ruleset = Some(e.value()).filter(|ruleset| !ruleset.is_empty()),
+                option { value: "", "any ruleset" }
+                for ruleset in rulesets {
+                    option { value: "{ruleset}", selected: current.ruleset.as_ref() == Some(&ruleset), "{ruleset}" }
+                }
+            }
+            select {
+                onchange: move |e| {
+                    let sort = SortOrder::ALL.into_iter().find(|sort| sort.name() == e.value()).unwrap_or_default();
+                    filter.write().sort = sort;
+                },
+                for sort in SortOrder::ALL {
+                    option { value: sort.name(), selected: current.sort == sort, "sort by {sort.name()}" }
+                }
+            }
+        }
+    }
+}
@@ -6,16 +6,9 @@ pub const MAIN_MENU_DATA: &str = r#"
         "algebra0",
         "algebra1",
         "algebra2",
-        "algebra3"
-    ]
-  },
-  {
-    "name": "Algebra",
-    "problem_ids": [
-        "algebra_simplify0",
-        "algebra_simplify1",
-        "algebra_simplify2",
-        "algebra_simplify3"
+        "algebra3",
+        "algebra4",
+        "algebra5"
     ]
   },
   {
@@ -27,59 +20,126 @@ pub const MAIN_MENU_DATA: &str = r#"
 ]
 "#;
 
+pub const COURSES_DATA: &str = r#"
+[
+  {
+    "name": "Algebra from the start",
+    "units": [
+      {
+        "id": "equations",
+        "name": "Equations",
+        "lessons": [
+          {
+            "id": "linear_equations",
+            "name": "Linear equations",
+            "problem_ids": ["algebra0", "algebra1"]
+          },
+          {
+            "id": "systems",
+            "name": "Systems of equations",
+            "problem_ids": ["algebra3"]
+          }
+        ]
+      },
+      {
+        "id": "expressions",
+        "name": "Expressions",
+        "prerequisites": [],
+        "lessons": [
+          {
+            "id": "like_terms",
+            "name": "Like terms",
+            "problem_ids": ["algebra2"]
+          },
+          {
+            "id": "powers_and_roots",
+            "name": "Powers and roots",
+            "prerequisites": ["like_terms", "linear_equations"],
+            "problem_ids": ["algebra4", "algebra5"]
+          }
+        ]
+      }
+    ]
+  }
+]
+"#;
+
 pub const PROBLEMS_DATA_MAP: &str = r#"
 {
     "algebra0": {
         "label": "Solve for x",
         "sublabel": "x + 3 = 5",
-        "rule": "algebra",
+        "description": "Isolate $x$ by applying the same operation to both sides of the equation.",
+        "rule": "algebra_simplify",
         "variables": ["x"],
-        "initial_expressions": ["x + 3 = 5"]
+        "initial_expressions": ["x + 3 = 5"],
+        "solution": ["x = 2"],
+        "difficulty": "easy",
+        "tags": ["linear equation"],
+        "estimated_minutes": 2
     },
     "algebra1":{
         "label": "Solve for x",
         "sublabel": "2x - 1 = 3",
-        "rule": "algebra",
+        "rule": "algebra_simplify",
         "variables": ["x"],
-        "initial_expressions": ["(2 * x) - 1 = 3"]
+        "initial_expressions": ["(2 * x) - 1 = 3"],
+        "solution": ["x = 2"],
+        "difficulty": "easy",
+        "tags": ["linear equation"],
+        "estimated_minutes": 3
     },
     "algebra2": {
         "label": "Simplify the expression",
-        "rule": "algebra",
+        "rule": "algebra_simplify",
         "variables": ["x"],
-        "initial_expressions": ["(6 * x) + (-4) + (3 * x) + 1"]
+        "initial_expressions": ["(6 * x) + (-4) + (3 * x) + 1"],
+        "solution": ["(9 * x) + (-3)"],
+        "difficulty": "medium",
+        "tags": ["like terms"],
+        "estimated_minutes": 5
     },
     "algebra3": {
         "label": "SLETV example",
-        "rule": "algebra",
+        "description": "A **system of linear equations** in two variables.\n\nAdd the equations to eliminate $y$, solve $(2 * x) = 4$ for $x$, then substitute $x$ back into $x + y = 3$.",
+        "rule": "algebra_simplify",
         "variables": ["x", "y"],
-        "initial_expressions": ["x + y = 3", "x - y = 1"]
+        "initial_expressions": ["x + y = 3", "x - y = 1"],
+        "solution": ["x = 2", "y = 1"],
+        "difficulty": "hard",
+        "tags": ["system of equations"],
+        "estimated_minutes": 8
     },
-    "algebra_simplify0": {
-        "label": "Solve for x",
-        "sublabel": "x + 3 = 5",
+    "algebra4": {
+        "label": "Simplify the expression",
+        "sublabel": "x^2 * x^3",
         "rule": "algebra_simplify",
         "variables": ["x"],
-        "initial_expressions": ["x + 3 = 5"]
+        "initial_expressions": ["(x ^ 2) * (x ^ 3)"],
+        "solution": ["x ^ 5"],
+        "difficulty": "medium",
+        "tags": ["exponents"],
+        "estimated_minutes": 3
     },
-    "algebra_simplify1": {
-        "label": "Solve for x",
-        "sublabel": "2x - 1 = 3",
+    "algebra5": {
+        "label": "Simplify the expression",
+        "sublabel": "sqrt(x^2) + 0",
         "rule": "algebra_simplify",
         "variables": ["x"],
-        "initial_expressions": ["(2 * x) - 1 = 3"]
-    },
-    "algebra_simplify2": {
-        "label": "SLETV example",
-        "rule": "algebra_simplify",
-        "variables": ["x", "y"],
-        "initial_expressions": ["x + y = 3", "x - y = 1"]
+        "initial_expressions": ["sqrt(x ^ 2) + 0"],
+        "solution": ["abs(x)"],
+        "difficulty": "medium",
+        "tags": ["radicals", "absolute value"],
+        "estimated_minutes": 3
     },
     "logic0": {
         "label": "Simplify the expression",
         "rule": "logic",
         "variables": ["P", "Q"],
-        "initial_expressions": ["(~P | Q) & (P | Q)"]
+        "initial_expressions": ["(~P | Q) & (P | Q)"],
+        "difficulty": "medium",
+        "tags": ["boolean algebra"],
+        "estimated_minutes": 4
     }
 }
 "#;
@@ -88,11 +148,26 @@ pub const ALGEBRA_RULES: &str = r#"
 {
     "name": "algebra",
     "context": {
-        "unary_ops": ["-"],
-        "binary_ops": ["+", "-", "*", "/"],
+        "unary_ops": ["-", "sqrt", "abs", "sin", "cos", "tan", "ln"],
+        "binary_ops": ["+", "-", "*", "/", "^", "log"],
         "assoc_ops": ["+", "*"],
         "handle_numerics": true
     },
+    "presentation": {
+        "inverse_ops": [["+", "-"], ["*", "/"]],
+        "fraction_ops": ["/"],
+        "conceal_ops": ["*", "^"],
+        "op_precedence": ["-", "+", "/", "*", "^", "log"],
+        "functions": {
+            "sqrt": "radical",
+            "abs": "abs",
+            "sin": "named",
+            "cos": "named",
+            "tan": "named",
+            "ln": "named"
+        },
+        "base_functions": ["log"]
+    },
     "variations": [
         {"expr":  "A + B = B + A"},
         {"expr":  "A * B = B * A"}
@@ -105,71 +180,194 @@ pub const ALGEBRA_RULES: &str = r#"
         {
             "id": "add_zero",
             "expr": "X + 0 = X",
-            "label": "Addition with 0"
+            "label": "Addition with 0",
+            "examples": [
+                {"expr": "x + 0 = 2", "variables": ["x"], "select": [1], "expected": ["x = 2"]}
+            ]
         },
         {
             "id": "mul_one",
             "expr": "X * 1 = X",
-            "label": "Multiplication with 1"
+            "label": "Multiplication with 1",
+            "examples": [
+                {"expr": "x * 1 = 2", "variables": ["x"], "select": [1], "expected": ["x = 2"]}
+            ]
         },
         {
             "id": "mul_zero",
             "expr": "X * 0 = 0",
-            "label": "Multiplication with 0"
+            "label": "Multiplication with 0",
+            "examples": [
+                {"expr": "x * 0 = 2", "variables": ["x"], "select": [1], "expected": ["0 = 2"]}
+            ]
         },
         {
             "id": "sub_zero",
             "expr": "X - 0 = X",
-            "label": "Subtraction by 0"
+            "label": "Subtraction by 0",
+            "examples": [
+                {"expr": "x - 0 = 2", "variables": ["x"], "select": [1], "expected": ["x = 2"]}
+            ]
         },
         {
             "id": "div_one",
             "expr": "X / 1 = X",
-            "label": "Division by 1"
+            "label": "Division by 1",
+            "examples": [
+                {"expr": "x / 1 = 2", "variables": ["x"], "select": [0, 1], "expected": ["x = 2"]}
+            ]
         },
         {
             "id": "sub_self",
             "expr": "X - X = 0",
-            "label": "Self subtraction"
+            "label": "Self subtraction",
+            "examples": [
+                {"expr": "x - x = 1", "variables": ["x"], "select": [1], "expected": ["0 = 1"]}
+            ]
         },
         {
             "id": "add_negative_self",
             "expr": "X + (-X) = 0",
-            "label": "Self subtraction"
+            "label": "Self subtraction",
+            "examples": [
+                {"expr": "x + (-x) = 1", "variables": ["x"], "select": [1], "expected": ["0 = 1"]}
+            ]
         },
         {
             "id": "factor_out_minus_right",
             "expr": "X * (-Y) = -(X * Y)",
             "label": "Factor out the minus sign",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "x * (-y) = 2", "variables": ["x", "y"], "select": [1], "expected": ["-(x * y) = 2"]}
+            ]
         },
         {
             "id": "factor_out_minus_left",
             "expr": "(-X) * Y = -(X * Y)",
             "label": "Factor out the minus sign",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "(-x) * y = 2", "variables": ["x", "y"], "select": [2], "expected": ["-(x * y) = 2"]}
+            ]
         },
         {
             "id": "add_self",
             "expr": "X + X = 2 * X",
-            "label": "Self addition"
+            "label": "Self addition",
+            "examples": [
+                {"expr": "x + x = 4", "variables": ["x"], "select": [1], "expected": ["2 * x = 4"]}
+            ]
         },
         {
             "id": "distribution",
             "expr": "X * (A_i + ...) = (X * A_i) + ...",
-            "label": "Distribution"
+            "label": "Distribution",
+            "examples": [
+                {"expr": "x * (y + 1) = 2", "variables": ["x", "y"], "select": [1], "expected": ["(x * y) + (x * 1) = 2"]}
+            ]
         },
         {
             "id": "factor_out_left",
             "expr": "(X * A_i) + ... = X * (A_i + ...)",
             "label": "Factoring Out",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "(x * y) + (x * 2) = 1", "variables": ["x", "y"], "select": [3], "expected": ["x * (y + 2) = 1"]}
+            ]
         },
         {
             "id": "factor_out_right",
             "expr": "(A_i * X) + ... = (A_i + ...) * X",
             "label": "Factoring Out",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "(y * x) + (2 * x) = 1", "variables": ["x", "y"], "select": [3], "expected": ["(y + 2) * x = 1"]}
+            ]
+        },
+        {
+            "id": "pow_one",
+            "expr": "X ^ 1 = X",
+            "label": "Power of 1",
+            "examples": [
+                {"expr": "x ^ 1 = 2", "variables": ["x"], "select": [1], "expected": ["x = 2"]}
+            ]
+        },
+        {
+            "id": "pow_zero",
+            "expr": "X ^ 0 = 1",
+            "label": "Power of 0 (nonzero base)",
+            "examples": [
+                {"expr": "x ^ 0 = 2", "variables": ["x"], "select": [1], "expected": ["1 = 2"]}
+            ]
+        },
+        {
+            "id": "mul_self",
+            "expr": "X * X = X ^ 2",
+            "label": "Self multiplication",
+            "examples": [
+                {"expr": "x * x = 4", "variables": ["x"], "select": [1], "expected": ["x ^ 2 = 4"]}
+            ]
+        },
+        {
+            "id": "pow_product",
+            "expr": "(X ^ A) * (X ^ B) = X ^ (A + B)",
+            "label": "Product of powers",
+            "examples": [
+                {"expr": "(x ^ a) * (x ^ a) = 1", "variables": ["x", "a"], "select": [3], "expected": ["x ^ (a + a) = 1"]}
+            ]
+        },
+        {
+            "id": "pow_power",
+            "expr": "(X ^ A) ^ B = X ^ (A * B)",
+            "label": "Power of a power",
+            "variations": [],
+            "examples": [
+                {"expr": "(x ^ a) ^ b = 1", "variables": ["x", "a", "b"], "select": [3], "expected": ["x ^ (a * b) = 1"]}
+            ]
+        },
+        {
+            "id": "log_one",
+            "expr": "B log 1 = 0",
+            "label": "Logarithm of 1",
+            "variations": [],
+            "examples": [
+                {"expr": "b log 1 = x", "variables": ["b", "x"], "select": [1], "expected": ["0 = x"]}
+            ]
+        },
+        {
+            "id": "log_self",
+            "expr": "B log B = 1",
+            "label": "Logarithm of the base",
+            "variations": [],
+            "examples": [
+                {"expr": "b log b = x", "variables": ["b", "x"], "select": [1], "expected": ["1 = x"]}
+            ]
+        },
+        {
+            "id": "log_of_power",
+            "expr": "B log (B ^ X) = X",
+            "label": "Logarithm of a power of the base",
+            "variations": [],
+            "examples": [
+                {"expr": "b log (b ^ x) = y", "variables": ["b", "x", "y"], "select": [1], "expected": ["x = y"]}
+            ]
+        },
+        {
+            "id": "sqrt_of_square",
+            "expr": "sqrt(X ^ 2) = abs(X)",
+            "label": "Square root of a square",
+            "examples": [
+                {"expr": "sqrt(x ^ 2) = 1", "variables": ["x"], "select": [0], "expected": ["abs(x) = 1"]}
+            ]
+        },
+        {
+            "id": "square_of_sqrt",
+            "expr": "sqrt(X) ^ 2 = X",
+            "label": "Square of a square root",
+            "examples": [
+                {"expr": "sqrt(x) ^ 2 = 1", "variables": ["x"], "select": [2], "expected": ["x = 1"]}
+            ]
         }
     ]
 }
@@ -177,98 +375,141 @@ pub const ALGEBRA_RULES: &str = r#"
 
 pub const ALGEBRA_SIMPLIFY_RULES: &str = r#"
 {
-    "name": "algebra",
+    "extends": "algebra",
+    "override_rules": {
+        "add_zero": {"auto": true},
+        "mul_one": {"auto": true},
+        "mul_zero": {"auto": true},
+        "sub_zero": {"auto": true},
+        "div_one": {"auto": true},
+        "sub_self": {"auto": true},
+        "add_negative_self": {"auto": true},
+        "pow_one": {"auto": true}
+    }
+}
+"#;
+
+pub const LOGIC_RULES: &str = r#"
+{
+    "name": "logic",
     "context": {
-        "unary_ops": ["-"],
-        "binary_ops": ["+", "-", "*", "/"],
-        "assoc_ops": ["+", "*"],
-        "handle_numerics": true
+        "unary_ops": ["~"],
+        "binary_ops": ["&", "|"],
+        "assoc_ops": ["&", "|"],
+        "handle_numerics": false
+    },
+    "presentation": {
+        "op_precedence": ["|", "&", "~"],
+        "symbols": {
+            "&": "∧",
+            "|": "∨",
+            "~": "¬"
+        }
     },
     "variations": [
-        {"expr":  "A + B = B + A"},
-        {"expr":  "A * B = B * A"}
-    ],
-    "normalization": [
-        {"expr": "NOTE: this normalization fields is not being used yet"},
-        {"expr_prefix": "=(-(0),0)"}
+        {"expr":  "A & B = B & A"},
+        {"expr":  "A | B = B | A"}
     ],
     "rules": [
         {
-            "id": "add_zero",
-            "expr": "X + 0 = X",
-            "label": "Addition with 0",
-            "auto": true
+            "id": "and_idempotent",
+            "expr": "X & X = X",
+            "label": "Idempotence",
+            "examples": [
+                {"expr": "P & P", "variables": ["P"], "select": [1], "expected": ["P"]}
+            ]
         },
         {
-            "id": "mul_one",
-            "expr": "X * 1 = X",
-            "label": "Multiplication with 1",
-            "auto": true
+            "id": "or_idempotent",
+            "expr": "X | X = X",
+            "label": "Idempotence",
+            "examples": [
+                {"expr": "P | P", "variables": ["P"], "select": [1], "expected": ["P"]}
+            ]
         },
         {
-            "id": "mul_zero",
-            "expr": "X * 0 = 0",
-            "label": "Multiplication with 0",
-            "auto": true
+            "id": "double_negation",
+            "expr": "~(~X) = X",
+            "label": "Double negation",
+            "examples": [
+                {"expr": "~(~P)", "variables": ["P"], "select": [0], "expected": ["P"]}
+            ]
         },
         {
-            "id": "sub_zero",
-            "expr": "X - 0 = X",
-            "label": "Subtraction by 0",
-            "auto": true
+            "id": "and_absorption",
+            "expr": "X & (X | Y) = X",
+            "label": "Absorption",
+            "examples": [
+                {"expr": "P & (P | Q)", "variables": ["P", "Q"], "select": [1], "expected": ["P"]}
+            ]
         },
         {
-            "id": "div_one",
-            "expr": "X / 1 = X",
-            "label": "Division by 1",
-            "auto": true
+            "id": "or_absorption",
+            "expr": "X | (X & Y) = X",
+            "label": "Absorption",
+            "examples": [
+                {"expr": "P | (P & Q)", "variables": ["P", "Q"], "select": [1], "expected": ["P"]}
+            ]
         },
         {
-            "id": "sub_self",
-            "expr": "X - X = 0",
-            "label": "Self subtraction",
-            "auto": true
+            "id": "de_morgan_and",
+            "expr": "~(X & Y) = (~X) | (~Y)",
+            "label": "De Morgan's law",
+            "variations": [],
+            "examples": [
+                {"expr": "~(P & Q)", "variables": ["P", "Q"], "select": [0], "expected": ["(~P) | (~Q)"]}
+            ]
         },
         {
-            "id": "add_negative_self",
-            "expr": "X + (-X) = 0",
-            "label": "Self subtraction",
-            "auto": true
+            "id": "de_morgan_or",
+            "expr": "~(X | Y) = (~X) & (~Y)",
+            "label": "De Morgan's law",
+            "variations": [],
+            "examples": [
+                {"expr": "~(P | Q)", "variables": ["P", "Q"], "select": [0], "expected": ["(~P) & (~Q)"]}
+            ]
         },
         {
-            "id": "factor_out_minus_right",
-            "expr": "X * (-Y) = -(X * Y)",
-            "label": "Factor out the minus sign",
-            "variations": []
+            "id": "and_distribution",
+            "expr": "X & (A_i | ...) = (X & A_i) | ...",
+            "label": "Distribution",
+            "examples": [
+                {"expr": "P & (Q | R)", "variables": ["P", "Q", "R"], "select": [1], "expected": ["(P & Q) | (P & R)"]}
+            ]
         },
         {
-            "id": "factor_out_minus_left",
-            "expr": "(-X) * Y = -(X * Y)",
-            "label": "Factor out the minus sign",
-            "variations": []
+            "id": "or_distribution",
+            "expr": "X | (A_i & ...) = (X | A_i) & ...",
+            "label": "Distribution",
+            "examples": [
+                {"expr": "P | (Q & R)", "variables": ["P", "Q", "R"], "select": [1], "expected": ["(P | Q) & (P | R)"]}
+            ]
         },
         {
-            "id": "add_self",
-            "expr": "X + X = 2 * X",
-            "label": "Self addition"
-        },
-        {
-            "id": "distribution",
-            "expr": "X * (A_i + ...) = (X * A_i) + ...",
-            "label": "Distribution"
-        },
-        {
-            "id": "factor_out_left",
-            "expr": "(X * A_i) + ... = X * (A_i + ...)",
+            "id": "factor_out_and",
+            "expr": "(X & A_i) | ... = X & (A_i | ...)",
             "label": "Factoring Out",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "(P & Q) | (P & R)", "variables": ["P", "Q", "R"], "select": [3], "expected": ["P & (Q | R)"]}
+            ]
         },
         {
-            "id": "factor_out_right",
-            "expr": "(A_i * X) + ... = (A_i + ...) * X",
+            "id": "factor_out_or",
+            "expr": "(X | A_i) & ... = X | (A_i & ...)",
             "label": "Factoring Out",
-            "variations": []
+            "variations": [],
+            "examples": [
+                {"expr": "(P | Q) & (P | R)", "variables": ["P", "Q", "R"], "select": [3], "expected": ["P | (Q & R)"]}
+            ]
         }
     ]
 }
-"#;
\ No newline at end of file
+"#;
+
+/// bundled rulesets by name
+pub const RULESETS: [(&str, &str); 3] = [
+    ("algebra", ALGEBRA_RULES),
+    ("algebra_simplify", ALGEBRA_SIMPLIFY_RULES),
+    ("logic", LOGIC_RULES),
+];
@@ -0,0 +1,83 @@
+//! Why a line follows from the previous one: the rule pattern, what its variables stand for,
+//! and the part of the previous line it was applied to.
+//! The rule is matched at the symbols recorded for the step (see `rule_match`), when there is no recorded step
+//! or the rule does not match there the rules of the action are shown and where they were applied is unknown.
+use dioxus::prelude::*;
+use equaio::block::Block;
+use super::content;
+use super::presentation::Presentation;
+use super::rule_match::{self, RuleMatch};
+use super::rules::RuleExpression;
+use super::session::Step;
+use super::worksheet::{self, MathText, WorksheetData};
+
+#[component]
+pub fn JustificationPanel(
+    line_index: usize, seq_index: usize,
+    ws: Signal<equaio::worksheet::Worksheet>,
+    steps: Signal<Vec<Step>>
+) -> Element {
+    let content = content::use_content();
+    let ws_data = use_context::<WorksheetData>();
+    let block_ctx = use_context::<Presentation>().block_context();
+    let seq = ws.read().get(seq_index);
+    let action_str = seq.as_ref().and_then(|seq| seq.history.get(line_index)).map(|line| line.action.to_string()).unwrap_or_default();
+    let previous = seq.zip(line_index.checked_sub(1))
+        .and_then(|(seq, i)| seq.history.get(i).map(|line| Block::from_root_expression(&line.expr, &block_ctx)));
+    let step = steps.read().iter().find(|step| step.seq == seq_index && step.line == line_index).cloned();
+    let rule_match = previous.zip(step).and_then(|(previous, step)|
+        rule_match::match_step(&content.read(), &ws_data, &block_ctx, &action_str, &previous, &step));
+    let Some(RuleMatch { rule, bindings, target, .. }) = rule_match else {
+        let candidates = rule_match::candidate_rules(&content.read(), &ws_data, &action_str);
+        if candidates.is_empty() {
+            return rsx! { div { class: "expression-line-justification", "no rule found for \"{action_str}\"" } };
+        }
+        return rsx! {
+            div {
+                class: "expression-line-justification",
+                div {
+                    class: "justification-row",
+                    span { class: "justification-caption", if candidates.len() > 1 { "one of the rules" } else { "rule" } }
+                    for rule in candidates {
+                        RuleExpression { ruleset: ws_data.rule.clone(), expr: rule.expr }
+                    }
+                }
+                div {
+                    class: "justification-row",
+                    span { class: "justification-caption", "applied to" }
+                    span { "unknown" }
+                }
+            }
+        };
+    };
+    rsx! {
+        div {
+            class: "expression-line-justification",
+            div {
+                class: "justification-row",
+                span { class: "justification-caption", "rule" }
+                RuleExpression { ruleset: ws_data.rule.clone(), expr: rule.expr }
+            }
+            if !bindings.is_empty() {
+                div {
+                    class: "justification-row",
+                    span { class: "justification-caption", "where" }
+                    for (name, block) in bindings {
+                        span {
+                            class: "justification-binding",
+                            MathText { text: name.clone(), variables: vec![name] }
+                            span { " = " }
+                            worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "justification-row",
+                span { class: "justification-caption", "applied to" }
+                worksheet::Block { block: target, active_address: None, on_address_update: |_| {} }
+                span { class: "justification-caption", "in the previous line" }
+            }
+        }
+    }
+}
@@ -2,56 +2,158 @@
 #![allow(clippy::redundant_closure)]
 #![allow(clippy::needless_return)]
 mod json;
+mod presentation;
+mod extends;
+mod validate;
+// shared by the app and the headless cli, their components are left out of the cli
 mod worksheet;
+mod session;
+mod content;
+mod course;
+// the ui only
+#[cfg(not(feature = "cli"))]
 mod utils;
+#[cfg(not(feature = "cli"))]
+mod storage;
+#[cfg(not(feature = "cli"))]
+mod settings;
+#[cfg(not(feature = "cli"))]
+mod rules;
+#[cfg(not(feature = "cli"))]
+mod justification;
+#[cfg(not(feature = "cli"))]
+mod rule_match;
+#[cfg(not(feature = "cli"))]
+mod theme;
+#[cfg(not(feature = "cli"))]
+mod print;
+#[cfg(not(feature = "cli"))]
+mod desktop;
+#[cfg(not(feature = "cli"))]
+mod pwa;
+#[cfg(not(feature = "cli"))]
+mod embed;
+#[cfg(not(feature = "cli"))]
+mod share;
+#[cfg(not(feature = "cli"))]
+mod editor;
+#[cfg(not(feature = "cli"))]
+mod ruleset_editor;
+#[cfg(not(feature = "cli"))]
+mod filter;
+#[cfg(not(feature = "cli"))]
+mod description;
+#[cfg(not(feature = "cli"))]
+mod examples;
+#[cfg(feature = "cli")]
+mod cli;
 
-use std::collections::HashMap;
-
+#[cfg(not(feature = "cli"))]
 use dioxus::prelude::*;
+#[cfg(not(feature = "cli"))]
 use dioxus_logger::tracing::{info, Level};
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-struct CategoryData {
-    pub name: String,
-    pub problem_ids: Vec<String>,
-}
 
+#[cfg(not(feature = "cli"))]
 #[derive(Clone, PartialEq, Default)]
 enum Route {
     #[default]
     Home,
+    /// shown until the startup route is known
+    Startup,
+    Embed { target: embed::EmbedTarget },
     ProblemPage { problem_id: String },
+    Handout { category_name: String, variant: print::HandoutVariant },
+    Session { session: session::SessionData },
+    SharedLinkError { message: String },
+    ProblemEditor { problem_id: Option<String>, draft: Option<worksheet::WorksheetData> },
+    RulesetEditor,
+    /// the rules of a ruleset, only the ones a problem allows if `ws_data` is given
+    Rules { ruleset: String, ws_data: Option<worksheet::WorksheetData> },
+    Course { course_name: String },
 }
+#[cfg(not(feature = "cli"))]
 type Router = Signal<Vec<Route>>;
 
+#[cfg(feature = "cli")]
+fn main() {
+    std::process::exit(cli::run(std::env::args().skip(1).collect()));
+}
+
+#[cfg(not(feature = "cli"))]
 fn main() {
     // Init logger
     dioxus_logger::init(Level::INFO).expect("failed to init logger");
     info!("starting app");
+    #[cfg(feature = "desktop")]
+    LaunchBuilder::desktop()
+        .with_cfg(dioxus::desktop::Config::new().with_resource_directory("assets"))
+        .launch(App);
+    #[cfg(not(feature = "desktop"))]
     launch(App);
 }
 
+#[cfg(not(feature = "cli"))]
 fn App() -> Element {
-    let router: Router = use_signal(|| vec![Route::Home]);
+    let mut router: Router = use_signal(|| vec![Route::Startup]);
+    let content = content::use_content_provider();
+    // kept while visiting the problems
+    use_context_provider(|| Signal::new(filter::ProblemFilter::default()));
+    use_hook(move || spawn(async move {
+        let store = content.peek().clone();
+        let route = match embed::read_embed_target().await {
+            Some(target) => Route::Embed { target },
+            None => match share::read_shared_session(&store).await {
+                Some(Ok(session)) => Route::Session { session },
+                Some(Err(message)) => Route::SharedLinkError { message },
+                None => Route::Home,
+            },
+        };
+        router.set(vec![route]);
+    }));
+    theme::use_theme_provider();
+    settings::use_settings_provider();
+    course::use_progress_provider();
     rsx! {
+        theme::ThemeStyle {}
         link { rel: "stylesheet", href: "main.css" }
         link { rel: "stylesheet", href: "block.css" }
         link { rel: "stylesheet", href: "worksheet.css" }
         match router.read().last().cloned().unwrap_or_default() {
             Route::Home => rsx! { Home { router } },
+            Route::Startup => rsx! {},
+            Route::Embed { target } => rsx! { embed::EmbedPage { target } },
             Route::ProblemPage { problem_id } => rsx! { ProblemPage { router, problem_id } },
+            Route::Session { session } => rsx! { 
+                ProblemPage { router, problem_id: session.problem_id.clone().unwrap_or_default(), session } 
+            },
+            Route::SharedLinkError { message } => rsx! { share::SharedLinkErrorPage { router, message } },
+            Route::Handout { category_name, variant } => rsx! { HandoutPage { router, category_name, variant } },
+            Route::ProblemEditor { problem_id, draft } => rsx! { editor::ProblemEditorPage { router, problem_id, draft } },
+            Route::RulesetEditor => rsx! { ruleset_editor::RulesetEditorPage { router } },
+            Route::Rules { ruleset, ws_data } => rsx! { rules::RulesPage { router, ruleset, ws_data } },
+            Route::Course { course_name } => rsx! { course::CoursePage { router, course_name } },
         }
     }
 }
 
 
+#[cfg(not(feature = "cli"))]
 #[component]
 fn Home(router: Router) -> Element {
-    let categories: Result<Vec<CategoryData>, _> = serde_json::from_str(json::MAIN_MENU_DATA);
-    let categories = categories.unwrap_or_default();
-    let worksheet_data_map: HashMap<String, worksheet::WorksheetData> = serde_json::from_str(json::PROBLEMS_DATA_MAP).unwrap_or_default();
-    let convert_mathvar = |s: String| utils::convert_mathvar(s);
+    let content = content::use_content();
+    let content = content.read();
+    let filter = use_context::<Signal<filter::ProblemFilter>>();
+    let categories = content.categories()
+        .map(|cat| (cat.clone(), filter.read().apply(content.category_problems(&cat.name))))
+        .filter(|(_, problems)| !problems.is_empty())
+        .collect::<Vec<_>>();
+    let mut tags = content.categories()
+        .flat_map(|cat| content.category_problems(&cat.name))
+        .flat_map(|(_, ws_data)| ws_data.tags)
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    let ruleset_names = content.ruleset_names();
     rsx! {
         div {
             class: "navbar",
@@ -66,55 +168,222 @@ fn Home(router: Router) -> Element {
                     "EQUAIO"
                 }
             }
+            div {
+                class: "navbar-right",
+                pwa::InstallButton {}
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().push(Route::ProblemEditor { problem_id: None, draft: None }); },
+                    "new problem"
+                }
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().push(Route::RulesetEditor); },
+                    "rulesets"
+                }
+                desktop::OpenFileButton { router }
+                theme::ThemeToggle {}
+            }
         }
         div {
             class: "main-menu",
-            for cat in categories {
+            desktop::RecentFiles { router }
+            course::CourseList { router }
+            filter::FilterBar { filter, tags, rulesets: ruleset_names.clone() }
+            if categories.is_empty() {
+                div { class: "filter-empty", "No problem matches the filters" }
+            }
+            for (cat, problems) in categories {
                 div {
                     class: "category-container",
                     div { 
                         class: "category-header",
                         span { "{cat.name}" }
+                        div {
+                            class: "category-header-actions",
+                            button {
+                                class: "category-header-button",
+                                onclick: {
+                                    let category_name = cat.name.clone();
+                                    move |_| { router.write().push(Route::Handout { category_name: category_name.clone(), variant: print::HandoutVariant::Blank }); }
+                                },
+                                "handout"
+                            }
+                            button {
+                                class: "category-header-button",
+                                onclick: {
+                                    let category_name = cat.name.clone();
+                                    move |_| { router.write().push(Route::Handout { category_name: category_name.clone(), variant: print::HandoutVariant::SolutionKey }); }
+                                },
+                                "solution key"
+                            }
+                        }
                     }
-                    for id in cat.problem_ids {
-                        if let Some(ws_data) = worksheet_data_map.get(&id) {
-                            div {
-                                // to: Route::ProblemPage { problem_id: id.clone() },
-                                class: "category-button",
-                                onclick: move |_| { router.write().push(Route::ProblemPage { problem_id: id.clone() }); },
-                                span { "{ws_data.label.clone()}" }
-                                span { 
-                                    class: "problem-sublabel",
-                                    "{convert_mathvar(ws_data.sublabel.clone().unwrap_or_default())}" 
+                    for (id, ws_data) in problems {
+                        div {
+                            // to: Route::ProblemPage { problem_id: id.clone() },
+                            class: "category-button",
+                            onclick: {
+                                let problem_id = id.clone();
+                                move |_| { router.write().push(Route::ProblemPage { problem_id: problem_id.clone() }); }
+                            },
+                            span {
+                                "{ws_data.label.clone()}"
+                                ProblemMeta { ws_data: ws_data.clone() }
+                            }
+                            span { 
+                                class: "problem-sublabel",
+                                worksheet::MathText {
+                                    text: ws_data.sublabel.clone().unwrap_or_default(),
+                                    variables: ws_data.variables.clone()
+                                }
+                                if cat.name == content::LOCAL_CATEGORY_NAME {
+                                    button {
+                                        class: "category-header-button",
+                                        onclick: {
+                                            let problem_id = id.clone();
+                                            move |e: MouseEvent| {
+                                                e.stop_propagation();
+                                                router.write().push(Route::ProblemEditor { problem_id: Some(problem_id.clone()), draft: None });
+                                            }
+                                        },
+                                        "edit"
+                                    }
                                 }
                             }
                         }
                     }
                 }
             }
+            div {
+                class: "category-container",
+                div {
+                    class: "category-header",
+                    span { "Rulesets" }
+                }
+                for ruleset in ruleset_names {
+                    div {
+                        class: "category-button",
+                        onclick: {
+                            let ruleset = ruleset.clone();
+                            move |_| { router.write().push(Route::Rules { ruleset: ruleset.clone(), ws_data: None }); }
+                        },
+                        span { "{ruleset}" }
+                    }
+                }
+            }
         }
     }
 }
 
+/// Difficulty and estimated time of a problem
+#[cfg(not(feature = "cli"))]
+#[component]
+fn ProblemMeta(ws_data: worksheet::WorksheetData) -> Element {
+    let meta = ws_data.difficulty.map(|difficulty| difficulty.name().to_string()).into_iter()
+        .chain(ws_data.estimated_minutes.map(|minutes| format!("{} min", minutes)))
+        .collect::<Vec<_>>();
+    if meta.is_empty() { return rsx! {}; }
+    rsx! {
+        span { class: "problem-meta", "{meta.join(\" · \")}" }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
 #[component]
-fn ProblemPage(router: Router, problem_id: String) -> Element {
-    let problems_data_map: HashMap<String, worksheet::WorksheetData> = serde_json::from_str(json::PROBLEMS_DATA_MAP).unwrap_or_default();
+fn ProblemPage(router: Router, problem_id: String, session: Option<session::SessionData>) -> Element {
+    let content = content::use_content();
+    let ws_data = match &session {
+        Some(session) => Some(session.ws_data.clone()),
+        None => content.read().problem(&problem_id).cloned(),
+    };
+    let steps = use_signal(|| session.map(|session| session.steps).unwrap_or_default());
+    let mut print_mode = use_signal(|| false);
+    let mut progress = course::use_progress();
     
     rsx! {
-        div {
-            class: "navbar",
+        if *print_mode.read() {
+            print::PrintToolbar { on_exit: move |_| print_mode.set(false) }
+        } else {
             div {
-                class: "navbar-left",
-                button {
-                    class: "navbar-button",
-                    onclick: move |_| { router.write().pop(); },
-                    "<"
+                class: "navbar",
+                div {
+                    class: "navbar-left",
+                    button {
+                        class: "navbar-button",
+                        onclick: move |_| { router.write().pop(); },
+                        "<"
+                    }
+                }
+                div {
+                    class: "navbar-right",
+                    if let Some(ws_data) = ws_data.clone() {
+                        button {
+                            class: "navbar-button",
+                            title: "Rules of this problem",
+                            onclick: {
+                                let ws_data = ws_data.clone();
+                                move |_| { router.write().push(Route::Rules { ruleset: ws_data.rule.clone(), ws_data: Some(ws_data.clone()) }); }
+                            },
+                            "rules"
+                        }
+                        share::ShareButton {
+                            problem_id: problem_id.clone(),
+                            ws_data: ws_data.clone(), steps
+                        }
+                        desktop::SaveSessionButton {
+                            problem_id: problem_id.clone(),
+                            ws_data, steps
+                        }
+                    }
+                    button {
+                        class: "navbar-button",
+                        onclick: move |_| print_mode.set(true),
+                        "print"
+                    }
+                    settings::AnimationToggle {}
+                    theme::ThemeToggle {}
                 }
             }
         }
-        if let Some(ws_data) = problems_data_map.get(&problem_id) {
+        if let Some(ws_data) = ws_data {
+            if *print_mode.read() {
+                h2 { class: "print-title", "{ws_data.label}" }
+            }
+            if ws_data.description.is_some() {
+                if *print_mode.read() {
+                    description::Description { ws_data: ws_data.clone() }
+                } else {
+                    details {
+                        class: "description-panel",
+                        open: true,
+                        summary { "Instructions" }
+                        description::Description { ws_data: ws_data.clone() }
+                    }
+                }
+            }
+            if !*print_mode.read() && (!ws_data.allowed_rules.is_empty() || !ws_data.forbidden_rules.is_empty()) {
+                details {
+                    class: "rules-panel",
+                    summary { "Available rules" }
+                    ul {
+                        for rule in content.read().available_rules(&ws_data) {
+                            li { "{rule.label}" }
+                        }
+                    }
+                }
+            }
             worksheet::Worksheet {
-                ws_data: ws_data.clone(),
+                ws_data,
+                print_mode: *print_mode.read(),
+                steps,
+                on_solved: {
+                    let problem_id = problem_id.clone();
+                    move |_| {
+                        // drafts of the problem editor have no id
+                        if !problem_id.is_empty() { progress.write().mark_solved(&problem_id); }
+                    }
+                }
             }
         } else {
             div {
@@ -123,4 +392,28 @@ fn ProblemPage(router: Router, problem_id: String) -> Element {
         }
     }
     
+}
+
+#[cfg(not(feature = "cli"))]
+#[component]
+fn HandoutPage(router: Router, category_name: String, variant: print::HandoutVariant) -> Element {
+    let problems = content::use_content().read().category_problems(&category_name);
+    
+    rsx! {
+        print::PrintToolbar { on_exit: move |_| { router.write().pop(); } }
+        div {
+            class: "handout",
+            h2 {
+                class: "print-title",
+                "{category_name}"
+                if variant == print::HandoutVariant::SolutionKey { " (solution key)" }
+            }
+            if variant == print::HandoutVariant::Blank {
+                div { class: "handout-name-line", "Name:" }
+            }
+            for (i, (_, ws_data)) in problems.into_iter().enumerate() {
+                print::HandoutProblem { number: i + 1, ws_data, variant }
+            }
+        }
+    }
 }
\ No newline at end of file
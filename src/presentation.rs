@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use equaio::block::{Block, BlockTag, BlockType};
+#[cfg(not(feature = "cli"))]
+use super::utils::{self, MathToken};
+
+/// How a function-style unary operator (e.g. `sqrt(x)`, `sin(x)`) is drawn
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionStyle {
+    /// radical sign with an overline over the argument, e.g. `sqrt`
+    Radical,
+    /// vertical bars around the argument, e.g. `abs`
+    Abs,
+    /// upright function name followed by the parenthesized argument, e.g. `sin`, `log`
+    Named,
+}
+
+/// Ruleset specific presentation, read from the `"presentation"` section of the ruleset json
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Presentation {
+    /// pairs of `(op, inverse)`, e.g. `["+", "-"]`
+    #[serde(default)]
+    pub inverse_ops: Vec<(String, String)>,
+    #[serde(default)]
+    pub fraction_ops: Vec<String>,
+    #[serde(default)]
+    pub conceal_ops: Vec<String>,
+    /// operators ordered from the lowest to the highest precedence
+    #[serde(default)]
+    pub op_precedence: Vec<String>,
+    /// how a symbol is displayed, e.g. `"&": "∧"`
+    #[serde(default)]
+    pub symbols: HashMap<String, String>,
+    #[serde(default)]
+    pub functions: HashMap<String, FunctionStyle>,
+    /// binary operators drawn as a function of the right operand with the left one as subscript,
+    /// e.g. `b log x` as log_b(x)
+    #[serde(default)]
+    pub base_functions: Vec<String>,
+    /// declared variables of the worksheet, rendered in math italic
+    #[serde(skip)]
+    pub variables: Vec<String>,
+}
+
+impl Presentation {
+    /// The presentation of a ruleset json, the default one if it has no `presentation` section
+    pub fn from_ruleset_json(rulestr: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(rulestr).map_err(|err| err.to_string())?;
+        let Some(presentation) = value.get("presentation") else { return Ok(Presentation::default()); };
+        return serde_json::from_value(presentation.clone()).map_err(|err| format!("invalid presentation: {}", err));
+    }
+    pub fn block_context(&self) -> equaio::block::BlockContext {
+        equaio::block::BlockContext {
+            inverse_ops: self.inverse_ops.iter().cloned().collect(),
+            fraction_ops: self.fraction_ops.clone(),
+            conceal_ops: self.conceal_ops.clone(),
+            op_precedence: self.op_precedence.iter().enumerate().map(|(i, op)| (op.clone(), i)).collect(),
+        }
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn function_style(&self, symbol: &str) -> Option<FunctionStyle> {
+        if self.is_base_function(symbol) { return Some(FunctionStyle::Named); }
+        self.functions.get(symbol).copied()
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn is_base_function(&self, symbol: &str) -> bool {
+        self.base_functions.iter().any(|op| op == symbol)
+    }
+    pub fn with_variables(self, variables: Vec<String>) -> Self {
+        Presentation { variables, ..self }
+    }
+    #[cfg(not(feature = "cli"))]
+    pub fn display_symbol(&self, symbol: &str) -> Vec<MathToken> {
+        match self.symbols.get(symbol) {
+            Some(display) => vec![MathToken::plain(display)],
+            None => utils::tokenize_math(symbol, &self.variables),
+        }
+    }
+}
+
+/// Plain text form of `block`, e.g. `(2 * x) - 1 = 3`
+pub fn block_to_text(block: &Block) -> String {
+    let children = block.children.clone().unwrap_or_default();
+    let text = match block.block_type {
+        BlockType::Symbol => block.symbol.clone().unwrap_or_default(),
+        BlockType::FractionContainer => children.iter().map(|child| {
+            let text = block_to_text(child);
+            let is_grouped = matches!(child.block_type, BlockType::Symbol) || child.contains_tag(&BlockTag::Parentheses);
+            if is_grouped { text } else { format!("({})", text) }
+        }).collect::<Vec<_>>().join(" / "),
+        // unary operators and function applications
+        BlockType::HorizontalContainer if children.len() == 2 => {
+            let (op, argument) = (block_to_text(&children[0]), block_to_text(&children[1]));
+            let is_function = op.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if is_function && !argument.starts_with('(') { format!("{}({})", op, argument) } else { op + &argument }
+        },
+        BlockType::HorizontalContainer => children.iter().map(block_to_text).collect::<Vec<_>>().join(" "),
+    };
+    if block.contains_tag(&BlockTag::Parentheses) { return format!("({})", text); }
+    return text;
+}
@@ -0,0 +1,71 @@
+use dioxus::prelude::*;
+use equaio::block::Block;
+use super::content;
+use super::worksheet::{self, WorksheetData};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HandoutVariant {
+    /// problems with empty answer space
+    Blank,
+    /// problems with their solution in the answer space
+    SolutionKey,
+}
+
+/// Replaces the navbar in print layouts, hidden when printing
+#[component]
+pub fn PrintToolbar(on_exit: EventHandler<()>) -> Element {
+    rsx! {
+        div {
+            class: "print-toolbar",
+            button {
+                class: "navbar-button",
+                onclick: move |_| on_exit.call(()),
+                "<"
+            }
+            button {
+                class: "navbar-button",
+                onclick: move |_| { let _ = eval("window.print();"); },
+                "print"
+            }
+        }
+    }
+}
+
+#[component]
+pub fn HandoutProblem(number: usize, ws_data: WorksheetData, variant: HandoutVariant) -> Element {
+    let content = content::use_content();
+    let block_ctx = worksheet::use_presentation_provider(&ws_data).block_context();
+    let to_blocks = |expr_strs: &[String]| worksheet::parse_expressions(&content.read(), &ws_data, expr_strs).iter()
+        .map(|expr| Block::from_root_expression(expr, &block_ctx))
+        .collect::<Vec<_>>();
+    let problem_blocks = to_blocks(&ws_data.initial_expressions);
+    let solution_blocks = to_blocks(&ws_data.solution);
+    
+    rsx! {
+        div {
+            class: "handout-problem",
+            div {
+                class: "handout-problem-header",
+                span { class: "handout-problem-number", "{number}." }
+                span { "{ws_data.label}" }
+            }
+            div {
+                class: "handout-problem-expressions",
+                for block in problem_blocks {
+                    worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+                }
+            }
+            div {
+                class: "handout-answer-space",
+                if variant == HandoutVariant::SolutionKey {
+                    if solution_blocks.is_empty() {
+                        span { class: "handout-no-solution", "no solution provided" }
+                    }
+                    for block in solution_blocks {
+                        worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use dioxus::prelude::*;
+
+/// Offers to install the app, shown only when the browser fired `beforeinstallprompt` (see `assets/pwa.js`)
+#[component]
+pub fn InstallButton() -> Element {
+    let mut can_install = use_signal(|| false);
+    use_hook(move || spawn(async move {
+        let mut listener = eval(r#"
+            if (window.equaioCanInstall && window.equaioCanInstall()) { dioxus.send(true); }
+            window.addEventListener("equaio-installable", () => dioxus.send(true));
+            window.addEventListener("appinstalled", () => dioxus.send(false));
+            await new Promise(() => {});
+        "#);
+        while let Ok(value) = listener.recv().await {
+            can_install.set(value.as_bool().unwrap_or(false));
+        }
+    }));
+    
+    rsx! {
+        if *can_install.read() {
+            button {
+                class: "navbar-button",
+                onclick: move |_| {
+                    let _ = eval("window.equaioInstall();");
+                    can_install.set(false);
+                },
+                "install"
+            }
+        }
+    }
+}
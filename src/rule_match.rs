@@ -0,0 +1,230 @@
+//! Where the rule of a step was applied and what its pattern variables stood for.
+//! The rule is matched only at the symbols selected for the step, swapping operands where the variations
+//! of the rule say an operator is commutative (e.g. `A + B = B + A`).
+//! Nothing is guessed: without a recorded selection, or for patterns with `...`, the match is unknown.
+use std::mem::discriminant;
+use equaio::block::{Block, BlockContext, BlockType};
+use equaio::expression::Address;
+use super::content::{ContentStore, RuleInfo};
+use super::presentation::block_to_text;
+use super::session::{self, Step};
+use super::worksheet::{self, WorksheetData};
+
+/// The rule of a step matched at the part of the previous line it was applied to
+pub struct RuleMatch {
+    pub rule: RuleInfo,
+    /// pattern variables and the part of the previous line bound to each
+    pub bindings: Vec<(String, Block)>,
+    /// the part of the previous line the rule was applied to
+    pub target: Block,
+    /// the right hand side of the pattern
+    rhs: Block,
+    /// the block of the previous line replaced by the step, `target` itself or the n-ary block it is a run of
+    replaced: Block,
+    matcher: Matcher,
+}
+
+/// The declared variables of the problem are constants when they appear in a pattern
+fn is_pattern_variable(symbol: &str, variables: &[String]) -> bool {
+    let name = symbol.split('_').next().unwrap_or_default();
+    let is_variable_name = name.len() == 1 && name.chars().all(|c| c.is_ascii_uppercase());
+    return is_variable_name && !variables.iter().any(|variable| variable == symbol);
+}
+
+/// The operator of a variation of the form `A op B = B op A`
+fn commutative_op(variation: &str) -> Option<String> {
+    let (lhs, rhs) = variation.split_once('=')?;
+    let lhs = lhs.split_whitespace().collect::<Vec<_>>();
+    let rhs = rhs.split_whitespace().collect::<Vec<_>>();
+    return match (lhs.as_slice(), rhs.as_slice()) {
+        ([a, op, b], [b2, op2, a2]) if a == a2 && b == b2 && op == op2 && a != b => Some(op.to_string()),
+        _ => None,
+    };
+}
+
+/// Every order of `0..n` starting with the given order, only the given order for long operand lists
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 || n > 5 { return vec![(0..n).collect()]; }
+    let mut orders = vec![];
+    for order in permutations(n - 1) {
+        for i in (0..n).rev() {
+            let mut order = order.clone();
+            order.insert(i, n - 1);
+            orders.push(order);
+        }
+    }
+    return orders;
+}
+
+fn subblocks(block: &Block) -> Vec<&Block> {
+    let mut blocks = vec![block];
+    blocks.extend(block.children.iter().flatten().flat_map(subblocks));
+    return blocks;
+}
+
+/// The smallest block containing every address, an operator alone stands for the expression it belongs to
+fn enclosing_block<'a>(block: &'a Block, parent: Option<&'a Block>, addresses: &[Address]) -> &'a Block {
+    for child in block.children.iter().flatten() {
+        let symbols = session::symbol_addresses(child);
+        if addresses.iter().all(|addr| symbols.contains(addr)) { return enclosing_block(child, Some(block), addresses); }
+    }
+    let is_operator = block.symbol.as_deref().is_some_and(|symbol| !symbol.chars().any(|c| c.is_alphanumeric()));
+    return match parent {
+        Some(parent) if is_operator => parent,
+        _ => block,
+    };
+}
+
+struct Matcher {
+    variables: Vec<String>,
+    /// operators whose operands can be reordered, from the variations of the rule
+    commutative_ops: Vec<String>,
+}
+
+impl Matcher {
+    fn new(ws_data: &WorksheetData, variations: &[String]) -> Self {
+        Matcher {
+            variables: ws_data.variables.clone(),
+            commutative_ops: variations.iter().filter_map(|variation| commutative_op(variation)).collect(),
+        }
+    }
+    /// The operator of an n-ary block `a op b op c` if the variations allow reordering its operands
+    fn commutative_operator(&self, children: &[Block]) -> Option<String> {
+        if children.len() < 3 || children.len().is_multiple_of(2) { return None; }
+        let op = children[1].symbol.clone()?;
+        let is_same_op = children.iter().skip(1).step_by(2).all(|child| child.symbol.as_deref() == Some(op.as_str()));
+        return Some(op).filter(|op| is_same_op && self.commutative_ops.contains(op));
+    }
+
+    fn match_block(&self, pattern: &Block, block: &Block, bindings: &mut Vec<(String, Block)>) -> bool {
+        if let BlockType::Symbol = pattern.block_type {
+            let symbol = pattern.symbol.clone().unwrap_or_default();
+            if !is_pattern_variable(&symbol, &self.variables) {
+                return matches!(block.block_type, BlockType::Symbol) && block.symbol.as_deref() == Some(symbol.as_str());
+            }
+            if let Some((_, bound)) = bindings.iter().find(|(name, _)| *name == symbol) {
+                return block_to_text(bound) == block_to_text(block);
+            }
+            bindings.push((symbol, block.clone()));
+            return true;
+        }
+        if discriminant(&pattern.block_type) != discriminant(&block.block_type) { return false; }
+        let pattern_children = pattern.children.clone().unwrap_or_default();
+        let children = block.children.clone().unwrap_or_default();
+        if pattern_children.len() != children.len() { return false; }
+        let orders = match self.commutative_operator(&children) {
+            Some(_) => permutations(children.len() / 2 + 1),
+            None => vec![(0..children.len() / 2 + 1).collect()],
+        };
+        for order in orders {
+            // operands at even positions are reordered, the operators stay in place
+            let reordered = (0..children.len())
+                .map(|i| if i.is_multiple_of(2) { &children[order[i / 2] * 2] } else { &children[i] })
+                .collect::<Vec<_>>();
+            let mut attempt = bindings.clone();
+            if pattern_children.iter().zip(reordered).all(|(p, b)| self.match_block(p, b, &mut attempt)) {
+                *bindings = attempt;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Match `lhs` at `target`, or at a run of operands of an n-ary `target` containing every selected child
+    fn match_target(&self, lhs: &Block, target: &Block, selected: &[Address]) -> Option<(Block, Vec<(String, Block)>)> {
+        let mut bindings = vec![];
+        if self.match_block(lhs, target, &mut bindings) { return Some((target.clone(), bindings)); }
+        let (Some(pattern_children), Some(children)) = (&lhs.children, &target.children) else { return None; };
+        let is_run_pattern = matches!(lhs.block_type, BlockType::HorizontalContainer) && !pattern_children.len().is_multiple_of(2);
+        if !is_run_pattern || pattern_children.len() >= children.len() { return None; }
+        let selected_children = children.iter().enumerate()
+            .filter(|(_, child)| session::symbol_addresses(child).iter().any(|addr| selected.contains(addr)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        for start in (0..=children.len() - pattern_children.len()).step_by(2) {
+            let end = start + pattern_children.len();
+            if !selected_children.iter().all(|i| (start..end).contains(i)) { continue; }
+            let mut run = target.clone();
+            run.children = Some(children[start..end].to_vec());
+            let mut bindings = vec![];
+            if self.match_block(lhs, &run, &mut bindings) { return Some((run, bindings)); }
+        }
+        return None;
+    }
+}
+
+/// `(lhs, rhs)` of the rule pattern `expr`, parsed with the ruleset named `ruleset`
+fn pattern_sides(content: &ContentStore, ruleset: &str, block_ctx: &BlockContext, expr: &str) -> Option<(Block, Block)> {
+    let pattern_data = WorksheetData { rule: ruleset.to_string(), ..Default::default() };
+    let parsed = worksheet::parse_expressions(content, &pattern_data, &[expr.to_string()]);
+    let block = Block::from_root_expression(parsed.first()?, block_ctx);
+    return match block.children.as_deref() {
+        Some([lhs, eq, rhs]) if eq.symbol.as_deref() == Some("=") => Some((lhs.clone(), rhs.clone())),
+        _ => None,
+    };
+}
+
+/// The rules of the ruleset of `ws_data` labelled `label`, the rules an action with that label can come from
+pub fn candidate_rules(content: &ContentStore, ws_data: &WorksheetData, label: &str) -> Vec<RuleInfo> {
+    return content.available_rules(ws_data).into_iter().filter(|rule| rule.label == label).collect();
+}
+
+/// Match the rule of the action labelled `label`, applied by `step` on `previous`
+pub fn match_step(
+    content: &ContentStore, ws_data: &WorksheetData, block_ctx: &BlockContext,
+    label: &str, previous: &Block, step: &Step
+) -> Option<RuleMatch> {
+    let symbols = session::symbol_addresses(previous);
+    let selected = step.symbols.iter().map(|i| symbols.get(*i).cloned()).collect::<Option<Vec<_>>>()?;
+    if selected.is_empty() { return None; }
+    let replaced = enclosing_block(previous, None, &selected);
+    for rule in candidate_rules(content, ws_data, label) {
+        let Some((lhs, rhs)) = pattern_sides(content, &ws_data.rule, block_ctx, &rule.expr) else { continue; };
+        let variations = rule.variations.clone().unwrap_or_else(|| content.ruleset_variations(&ws_data.rule));
+        let matcher = Matcher::new(ws_data, &variations);
+        if let Some((target, bindings)) = matcher.match_target(&lhs, replaced, &selected) {
+            return Some(RuleMatch { rule, bindings, target, rhs, replaced: replaced.clone(), matcher });
+        }
+    }
+    return None;
+}
+
+fn symbol_text(block: &Block) -> String {
+    block.symbol.clone().unwrap_or_default()
+}
+
+/// Pairs of addresses of the symbols of `from` kept at the same place with the same text in `to`
+pub fn unchanged_symbols(from: &Block, to: &Block) -> Vec<(Address, Address)> {
+    let to_symbols = session::symbol_blocks(to);
+    return session::symbol_blocks(from).into_iter()
+        .filter(|symbol| to_symbols.iter().any(|other| other.address == symbol.address && symbol_text(other) == symbol_text(symbol)))
+        .map(|symbol| (symbol.address.clone(), symbol.address.clone()))
+        .collect();
+}
+
+impl RuleMatch {
+    /// Pairs of `(address in previous, address in produced)` of the symbols that are the same term,
+    /// where `produced` is the line the step produced from `previous`.
+    /// The symbols outside the replaced block keep their address, the ones bound to a pattern variable
+    /// move to where the variable is on the right hand side.
+    pub fn symbol_pairs(&self, previous: &Block, produced: &Block) -> Vec<(Address, Address)> {
+        let replaced_symbols = session::symbol_addresses(&self.replaced);
+        let mut pairs = unchanged_symbols(previous, produced).into_iter()
+            .filter(|(addr, _)| !replaced_symbols.contains(addr))
+            .collect::<Vec<_>>();
+        // a run of an n-ary block shifts the operands after it, their new place is unknown
+        if self.target.children != self.replaced.children { return pairs; }
+        let Some(new_target) = subblocks(produced).into_iter().find(|block| block.address == self.replaced.address) else { return pairs; };
+        let mut new_bindings = vec![];
+        if !self.matcher.match_block(&self.rhs, new_target, &mut new_bindings) { return pairs; }
+        for (name, bound) in &self.bindings {
+            let Some((_, new_bound)) = new_bindings.iter().find(|(new_name, _)| new_name == name) else { continue; };
+            let (old_symbols, new_symbols) = (session::symbol_blocks(bound), session::symbol_blocks(new_bound));
+            let is_same_term = old_symbols.len() == new_symbols.len()
+                && old_symbols.iter().zip(&new_symbols).all(|(old, new)| symbol_text(old) == symbol_text(new));
+            if !is_same_term { continue; }
+            pairs.extend(old_symbols.iter().zip(&new_symbols).map(|(old, new)| (old.address.clone(), new.address.clone())));
+        }
+        return pairs;
+    }
+}
@@ -0,0 +1,103 @@
+use dioxus::prelude::*;
+use equaio::block::Block;
+use super::content;
+use super::worksheet::{self, WorksheetData};
+use super::{Route, Router};
+
+/// A rule pattern such as `X + 0 = X` rendered as math, or as text if it does not parse
+#[component]
+pub fn RuleExpression(ruleset: String, expr: String) -> Element {
+    let content = content::use_content();
+    let ws_data = WorksheetData { rule: ruleset, ..Default::default() };
+    let block_ctx = worksheet::use_presentation_provider(&ws_data).block_context();
+    let block = worksheet::parse_expressions(&content.read(), &ws_data, &[expr.clone()]).first()
+        .map(|parsed| Block::from_root_expression(parsed, &block_ctx));
+    rsx! {
+        if let Some(block) = block {
+            worksheet::Block { block, active_address: None, on_address_update: |_| {} }
+        } else {
+            span { "{expr}" }
+        }
+    }
+}
+
+/// The rules of the ruleset named `ruleset` with their patterns and variations,
+/// only the ones that the problem `ws_data` allows if it is given
+#[component]
+pub fn RulesPage(router: Router, ruleset: String, ws_data: Option<WorksheetData>) -> Element {
+    let content = content::use_content();
+    let rules = match &ws_data {
+        Some(ws_data) => content.read().available_rules(ws_data),
+        None => content.read().rules(&ruleset),
+    };
+    let is_restricted = ws_data.as_ref().is_some_and(|ws_data| !ws_data.allowed_rules.is_empty() || !ws_data.forbidden_rules.is_empty());
+    let ruleset_variations = content.read().ruleset_variations(&ruleset);
+    rsx! {
+        div {
+            class: "navbar",
+            div {
+                class: "navbar-left",
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().pop(); },
+                    "<"
+                }
+            }
+            div {
+                class: "navbar-center",
+                h2 { "Rules of {ruleset}" }
+            }
+        }
+        div {
+            class: "editor",
+            if is_restricted {
+                div {
+                    span { class: "rule-variations", "only the rules this problem allows " }
+                    button {
+                        class: "category-header-button",
+                        onclick: {
+                            let ruleset = ruleset.clone();
+                            move |_| { router.write().push(Route::Rules { ruleset: ruleset.clone(), ws_data: None }); }
+                        },
+                        "show all"
+                    }
+                }
+            }
+            if !ruleset_variations.is_empty() {
+                div {
+                    class: "rule-card",
+                    span { "Every rule also applies in the forms given by" }
+                    for expr in ruleset_variations {
+                        RuleExpression { ruleset: ruleset.clone(), expr }
+                    }
+                }
+            }
+            for rule in rules {
+                div {
+                    class: "rule-card",
+                    div {
+                        class: "ruleset-editor-rule-header",
+                        span { "{rule.label}" }
+                        span { class: "ruleset-editor-rule-id", "{rule.id}" }
+                        if rule.auto {
+                            span { class: "ruleset-editor-tag", "automatic" }
+                        }
+                    }
+                    RuleExpression { ruleset: ruleset.clone(), expr: rule.expr }
+                    match rule.variations {
+                        None => rsx! {},
+                        Some(variations) if variations.is_empty() => rsx! {
+                            span { class: "rule-variations", "without the variations of the ruleset" }
+                        },
+                        Some(variations) => rsx! {
+                            span { class: "rule-variations", "variations" }
+                            for expr in variations {
+                                RuleExpression { ruleset: ruleset.clone(), expr }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
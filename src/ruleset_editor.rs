@@ -0,0 +1,367 @@
+use dioxus::prelude::*;
+use serde_json::{json, Map, Value};
+use super::content::{self, SCRATCH_RULESET_NAME};
+use super::examples;
+use super::rules;
+use super::storage;
+use super::validate;
+use super::worksheet::{self, WorksheetData};
+use super::Router;
+
+fn rule_str(rule: &Value, field: &str) -> String {
+    rule.get(field).and_then(|value| value.as_str()).unwrap_or_default().to_string()
+}
+/// The expressions of the own variations of `rule`, `None` if it uses the variations of the ruleset
+fn rule_variations(rule: &Value) -> Option<Vec<String>> {
+    let variations = rule.get("variations")?.as_array()?;
+    return Some(variations.iter().map(|variation| rule_str(variation, "expr")).collect());
+}
+fn to_rulestr(base: &Value, name: &str, rules: &[Value]) -> String {
+    let mut value = base.clone();
+    value["name"] = name.into();
+    value["rules"] = Value::Array(rules.to_vec());
+    return serde_json::to_string_pretty(&value).unwrap_or_default();
+}
+/// The ruleset `name` with `rules`, loaded as `base`, written as a diff of the ruleset `parent_name` that resolves to `parent`
+fn to_diff_rulestr(parent_name: &str, parent: &Value, base: &Value, name: &str, rules: &[Value]) -> String {
+    let mut value = json!({ "extends": parent_name, "name": name });
+    for (field, field_value) in base.as_object().into_iter().flatten() {
+        if ["name", "rules"].contains(&field.as_str()) || parent.get(field) == Some(field_value) { continue; }
+        value[field] = field_value.clone();
+    }
+    let parent_rules = parent["rules"].as_array().cloned().unwrap_or_default();
+    let remove_rules = parent_rules.iter().filter_map(|rule| rule.get("id"))
+        .filter(|id| !rules.iter().any(|rule| rule.get("id") == Some(*id)))
+        .cloned().collect::<Vec<_>>();
+    let mut override_rules = Map::new();
+    let mut added_rules = vec![];
+    for rule in rules {
+        let inherited = parent_rules.iter().find(|inherited| inherited.get("id").is_some() && inherited.get("id") == rule.get("id"));
+        match inherited {
+            Some(inherited) if inherited == rule => {},
+            // the changed fields are overridden, a rule that lost a field replaces the inherited one
+            Some(inherited) if inherited.as_object().into_iter().flatten().all(|(field, _)| rule.get(field).is_some()) => {
+                let changed = rule.as_object().into_iter().flatten()
+                    .filter(|(field, field_value)| inherited.get(*field) != Some(*field_value))
+                    .map(|(field, field_value)| (field.clone(), field_value.clone()))
+                    .collect::<Map<_, _>>();
+                override_rules.insert(rule_str(rule, "id"), Value::Object(changed));
+            },
+            _ => added_rules.push(rule.clone()),
+        }
+    }
+    if !remove_rules.is_empty() { value["remove_rules"] = Value::Array(remove_rules); }
+    if !override_rules.is_empty() { value["override_rules"] = Value::Object(override_rules); }
+    value["rules"] = Value::Array(added_rules);
+    return serde_json::to_string_pretty(&value).unwrap_or_default();
+}
+
+#[component]
+pub fn RulesetEditorPage(router: Router) -> Element {
+    let mut content = content::use_content();
+    let mut name = use_signal(String::new);
+    let mut base = use_signal(|| Value::Null);
+    // the ruleset that the edited one is saved as a diff of, `None` to save it whole
+    let mut parent = use_signal(|| None::<String>);
+    let mut rules = use_signal(Vec::<Value>::new);
+    let mut editing = use_signal(|| None::<usize>);
+    let mut scratch_expr = use_signal(|| "(x + 0) * 1 = 2".to_string());
+    let mut scratch_variables = use_signal(|| "x".to_string());
+    // incremented whenever the scratch ruleset is replaced
+    let mut version = use_signal(|| 0);
+    let mut is_saved = use_signal(|| false);
+
+    // start from a copy of a bundled ruleset, or edit a local one
+    let mut load = move |source_name: String| {
+        let rulestr = content.peek().ruleset_source(&source_name).to_string();
+        let value: Value = serde_json::from_str(&rulestr).unwrap_or_default();
+        rules.set(value["rules"].as_array().cloned().unwrap_or_default());
+        base.set(value);
+        let is_builtin = content.peek().is_builtin_ruleset(&source_name);
+        parent.set(if is_builtin { Some(source_name.clone()) } else { content.peek().local_ruleset_extends(&source_name) });
+        name.set(if is_builtin { format!("{}_custom", source_name) } else { source_name });
+        editing.set(None);
+        is_saved.set(false);
+    };
+    use_hook(|| load("algebra".to_string()));
+    use_effect(move || {
+        let rulestr = to_rulestr(&base.read(), SCRATCH_RULESET_NAME, &rules.read());
+        if validate::validate_ruleset(SCRATCH_RULESET_NAME, &rulestr).is_empty() {
+            content.write().set_scratch_ruleset(rulestr);
+            *version.write() += 1;
+        }
+    });
+
+    // parses every rule and applies every example, so only rerun when the ruleset changes
+    let rulestr = use_memo(move || to_rulestr(&base.read(), &name.read(), &rules.read()));
+    let ruleset_errors = use_memo(move || {
+        validate::validate_ruleset(&name.read(), &rulestr.read()).iter().map(|err| err.to_string()).collect::<Vec<_>>()
+    });
+    let example_errors = use_memo(move || {
+        if *version.read() == 0 { return vec![]; }
+        return examples::check_rule_examples(&content.peek(), SCRATCH_RULESET_NAME);
+    });
+    let rulestr = rulestr.read().clone();
+    let mut errors = ruleset_errors.read().clone();
+    if name.read().is_empty() || content.read().is_builtin_ruleset(&name.read()) || *name.read() == SCRATCH_RULESET_NAME {
+        errors.insert(0, "the name must be non-empty and different from the bundled rulesets".to_string());
+    }
+    if parent.read().as_ref() == Some(&*name.read()) {
+        errors.insert(0, "the name must be different from the ruleset it extends".to_string());
+    }
+    errors.extend(example_errors.read().iter().cloned());
+    let has_errors = !errors.is_empty();
+    // a saved ruleset that is invalid is not used, saving a valid one in its place fixes it
+    let saved_errors = content.read().local_ruleset_errors(&name.read()).to_vec();
+    let ruleset_names = content.read().ruleset_names();
+    let scratch_ws_data = WorksheetData {
+        label: "scratch".to_string(),
+        rule: SCRATCH_RULESET_NAME.to_string(),
+        variables: scratch_variables.read().split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+        initial_expressions: vec![scratch_expr.read().clone()],
+        ..Default::default()
+    };
+    let scratch_key = format!("{}:{}:{}", version, scratch_expr, scratch_variables);
+    let rule_rows = rules.read().iter().cloned().enumerate().map(|(i, rule)| {
+        let is_auto = rule.get("auto").and_then(|auto| auto.as_bool()).unwrap_or(false);
+        (i, rule_str(&rule, "id"), rule_str(&rule, "label"), rule_str(&rule, "expr"), is_auto, rule)
+    }).collect::<Vec<_>>();
+
+    rsx! {
+        div {
+            class: "navbar",
+            div {
+                class: "navbar-left",
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| { router.write().pop(); },
+                    "<"
+                }
+            }
+            div {
+                class: "navbar-right",
+                select {
+                    class: "navbar-button",
+                    onchange: move |e| load(e.value()),
+                    option { disabled: true, selected: true, "load ruleset" }
+                    for ruleset_name in ruleset_names {
+                        option { value: "{ruleset_name}", "{ruleset_name}" }
+                    }
+                }
+                button {
+                    class: "navbar-button",
+                    disabled: has_errors,
+                    onclick: {
+                        let rulestr = rulestr.clone();
+                        move |_| {
+                            let saved = match &*parent.peek() {
+                                Some(parent_name) => {
+                                    let parent_value = serde_json::from_str(content.peek().ruleset_source(parent_name)).unwrap_or_default();
+                                    to_diff_rulestr(parent_name, &parent_value, &base.peek(), &name.peek(), &rules.peek())
+                                },
+                                None => rulestr.clone(),
+                            };
+                            content.write().save_local_ruleset(name.peek().clone(), saved);
+                            is_saved.set(true);
+                        }
+                    },
+                    if *is_saved.read() { "saved" } else { "save" }
+                }
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| storage::download(&format!("{}.json", name.peek()), &rulestr),
+                    "export"
+                }
+            }
+        }
+        div {
+            class: "editor",
+            div {
+                class: "editor-form",
+                label { "name" }
+                input { value: "{name}", oninput: move |e| { name.set(e.value()); is_saved.set(false); } }
+            }
+            if has_errors {
+                ul {
+                    class: "editor-errors",
+                    for err in errors {
+                        li { "{err}" }
+                    }
+                }
+            }
+            if !saved_errors.is_empty() {
+                ul {
+                    class: "editor-errors",
+                    li { "the saved ruleset \"{name}\" is invalid and is not used:" }
+                    for err in saved_errors {
+                        li { "{err}" }
+                    }
+                }
+            }
+            div {
+                class: "ruleset-editor-rules",
+                for (i, id, label, expr, is_auto, rule) in rule_rows {
+                    div {
+                        class: "ruleset-editor-rule",
+                        div {
+                            class: "ruleset-editor-rule-header",
+                            if *version.read() > 0 {
+                                // remounted with the presentation of the new scratch ruleset
+                                rules::RuleExpression { key: "{version}", ruleset: SCRATCH_RULESET_NAME.to_string(), expr: expr.clone() }
+                            } else {
+                                span { "{expr}" }
+                            }
+                            span { class: "ruleset-editor-rule-id", "{id}" }
+                            span { "{label}" }
+                            if is_auto {
+                                span { class: "ruleset-editor-tag", "auto" }
+                            }
+                            button {
+                                class: "category-header-button",
+                                onclick: move |_| editing.set(if *editing.peek() == Some(i) { None } else { Some(i) }),
+                                if *editing.read() == Some(i) { "close" } else { "edit" }
+                            }
+                        }
+                        if *editing.read() == Some(i) {
+                            RuleForm {
+                                rule,
+                                on_change: move |rule| { rules.write()[i] = rule; is_saved.set(false); },
+                                on_delete: move |_| { rules.write().remove(i); editing.set(None); is_saved.set(false); }
+                            }
+                        }
+                    }
+                }
+                button {
+                    class: "category-header-button",
+                    onclick: move |_| {
+                        rules.write().push(json!({ "id": "new_rule", "expr": "", "label": "" }));
+                        editing.set(Some(rules.peek().len() - 1));
+                        is_saved.set(false);
+                    },
+                    "add rule"
+                }
+            }
+            div {
+                class: "editor-form",
+                label { "scratch expression" }
+                input { value: "{scratch_expr}", oninput: move |e| scratch_expr.set(e.value()) }
+                label { "variables (comma separated)" }
+                input { value: "{scratch_variables}", oninput: move |e| scratch_variables.set(e.value()) }
+            }
+            // select parts of the scratch expression to see which rules apply and what they produce
+            if *version.read() > 0 {
+                worksheet::Worksheet { key: "{scratch_key}", ws_data: scratch_ws_data }
+            }
+        }
+    }
+}
+
+#[component]
+fn RuleForm(rule: Value, on_change: EventHandler<Value>, on_delete: EventHandler<()>) -> Element {
+    let variations = rule_variations(&rule);
+    let is_auto = rule.get("auto").and_then(|auto| auto.as_bool()).unwrap_or(false);
+    let set_field = {
+        let rule = rule.clone();
+        move |field: &str, value: Value| {
+            let mut rule = rule.clone();
+            match value {
+                Value::Null => { if let Some(rule) = rule.as_object_mut() { rule.remove(field); } },
+                value => rule[field] = value,
+            }
+            on_change.call(rule);
+        }
+    };
+    let text_input = |field: &'static str| {
+        let set_field = set_field.clone();
+        let value = rule_str(&rule, field);
+        rsx! {
+            label { "{field}" }
+            input { value: "{value}", oninput: move |e| set_field(field, e.value().into()) }
+        }
+    };
+    rsx! {
+        div {
+            class: "editor-form",
+            {text_input("id")}
+            {text_input("label")}
+            {text_input("expr")}
+            label { "auto" }
+            input {
+                r#type: "checkbox",
+                checked: is_auto,
+                oninput: {
+                    let set_field = set_field.clone();
+                    move |e: FormEvent| set_field("auto", if e.value() == "true" { true.into() } else { Value::Null })
+                }
+            }
+            label { "ruleset variations" }
+            input {
+                r#type: "checkbox",
+                checked: variations.is_none(),
+                oninput: {
+                    let set_field = set_field.clone();
+                    move |e: FormEvent| set_field("variations", if e.value() == "true" { Value::Null } else { json!([]) })
+                }
+            }
+            if let Some(variations) = variations {
+                label { "variations (one per line)" }
+                textarea {
+                    rows: 3,
+                    value: variations.join("\n"),
+                    oninput: move |e| {
+                        let variations = e.value().lines().map(|s| s.trim()).filter(|s| !s.is_empty())
+                            .map(|expr| json!({ "expr": expr })).collect::<Vec<_>>();
+                        set_field("variations", Value::Array(variations));
+                    }
+                }
+            }
+            span {}
+            button {
+                class: "category-header-button",
+                onclick: move |_| on_delete.call(()),
+                "delete rule"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extends;
+
+    #[test]
+    fn diff_resolves_to_the_edited_ruleset() {
+        let parent = json!({
+            "name": "base",
+            "variations": [],
+            "rules": [
+                { "id": "a", "expr": "X + 0 = X", "label": "A" },
+                { "id": "b", "expr": "X * 1 = X", "label": "B" },
+                { "id": "c", "expr": "X * 0 = 0", "label": "C", "auto": true },
+                { "id": "d", "expr": "X - 0 = X", "label": "D" }
+            ]
+        });
+        let rules = vec![
+            json!({ "id": "a", "expr": "X + 0 = X", "label": "A" }),
+            json!({ "id": "b", "expr": "X * 1 = X", "label": "B", "auto": true }),
+            json!({ "id": "c", "expr": "X * 0 = 0", "label": "C" }),
+            json!({ "id": "e", "expr": "X / 1 = X", "label": "E" }),
+        ];
+        let diff = to_diff_rulestr("base", &parent, &parent, "custom", &rules);
+        let value: Value = serde_json::from_str(&diff).unwrap();
+        assert_eq!(value["extends"], "base");
+        assert_eq!(value["remove_rules"], json!(["d"]));
+        assert_eq!(value["override_rules"], json!({ "b": { "auto": true } }));
+        assert_eq!(value["rules"], json!([rules[2], rules[3]]));
+
+        let lookup = |name: &str| match name {
+            "base" => Some(parent.to_string()),
+            "custom" => Some(diff.clone()),
+            _ => None,
+        };
+        let resolved: Value = serde_json::from_str(&extends::resolve_ruleset("custom", &lookup).unwrap()).unwrap();
+        let full: Value = serde_json::from_str(&to_rulestr(&parent, "custom", &rules)).unwrap();
+        assert_eq!(resolved, full);
+    }
+}
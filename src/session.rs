@@ -0,0 +1,157 @@
+use equaio::block::{Block, BlockType};
+use equaio::expression::Address;
+// the sessions are only recorded and replayed by the app
+#[cfg(not(feature = "cli"))]
+use equaio::block::BlockContext;
+#[cfg(not(feature = "cli"))]
+use equaio::rule::RuleSet;
+#[cfg(not(feature = "cli"))]
+use equaio::worksheet::{WorkableExpressionSequence, Worksheet};
+#[cfg(not(feature = "cli"))]
+use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "cli"))]
+use super::worksheet::WorksheetData;
+
+/// An applied action. The selected addresses are stored as the indices of the selected symbols
+/// in the rendered line, so a step can be replayed on a freshly initialized worksheet.
+#[cfg(not(feature = "cli"))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Step {
+    /// index of the expression sequence in the worksheet
+    pub seq: usize,
+    /// index of the line produced by this step
+    pub line: usize,
+    pub symbols: Vec<usize>,
+    /// index of the action in the possible actions
+    pub action: usize,
+    /// label of the action, used to detect rulesets that changed since the step was recorded
+    pub label: String,
+    /// hash of the label, used instead of the label by shared links
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_hash: Option<u32>,
+    /// ids of the rules applied automatically when the step was applied, the ruleset's own if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_rules: Option<Vec<String>>,
+}
+
+#[cfg(not(feature = "cli"))]
+impl Step {
+    fn is_same_action(&self, label: &str) -> bool {
+        match self.label_hash {
+            Some(hash) => hash == label_hash(label),
+            None => self.label == label,
+        }
+    }
+}
+
+/// FNV-1a hash of an action label
+#[cfg(not(feature = "cli"))]
+pub fn label_hash(label: &str) -> u32 {
+    label.bytes().fold(0x811c9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+/// A problem together with the steps applied to it
+#[cfg(not(feature = "cli"))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionData {
+    #[serde(default)]
+    pub problem_id: Option<String>,
+    pub ws_data: WorksheetData,
+    pub steps: Vec<Step>,
+}
+
+#[cfg(not(feature = "cli"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayError {
+    pub step_index: usize,
+    /// label of the step, unknown for steps of shared links
+    pub label: Option<String>,
+}
+#[cfg(not(feature = "cli"))]
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "step {} (\"{}\") no longer applies", self.step_index + 1, label)?,
+            None => write!(f, "step {} no longer applies", self.step_index + 1)?,
+        }
+        write!(f, ", the ruleset might have changed since it was recorded")
+    }
+}
+
+/// Symbols of `block`, in reading order
+pub fn symbol_blocks(block: &Block) -> Vec<&Block> {
+    match block.block_type {
+        BlockType::Symbol => vec![block],
+        _ => block.children.iter().flatten().flat_map(symbol_blocks).collect(),
+    }
+}
+
+/// Addresses of the symbols of `block`, in reading order
+pub fn symbol_addresses(block: &Block) -> Vec<Address> {
+    symbol_blocks(block).into_iter().map(|symbol| symbol.address.clone()).collect()
+}
+
+#[cfg(not(feature = "cli"))]
+fn last_line_block(seq: &WorkableExpressionSequence, block_ctx: &BlockContext) -> Option<Block> {
+    let line = seq.history.last()?;
+    return Some(Block::from_root_expression(&line.expr, block_ctx));
+}
+
+/// Indices of `addresses` among the symbols of the last line of `seq`
+#[cfg(not(feature = "cli"))]
+pub fn selected_symbols(seq: &WorkableExpressionSequence, block_ctx: &BlockContext, addresses: &[Address]) -> Vec<usize> {
+    let Some(block) = last_line_block(seq, block_ctx) else { return vec![]; };
+    let symbols = symbol_addresses(&block);
+    return addresses.iter()
+        .filter_map(|addr| symbols.iter().position(|symbol_addr| symbol_addr == addr))
+        .collect();
+}
+
+/// Addresses of the symbols at `symbol_indices`, resolved on the last line of `seq`
+#[cfg(not(feature = "cli"))]
+pub fn symbol_indices_to_addresses(seq: &WorkableExpressionSequence, block_ctx: &BlockContext, symbol_indices: &[usize]) -> Option<Vec<Address>> {
+    let block = last_line_block(seq, block_ctx)?;
+    let symbols = symbol_addresses(&block);
+    return symbol_indices.iter().map(|i| symbols.get(*i).cloned()).collect();
+}
+
+/// Apply `steps` to `ws`, stopping at the first step that no longer applies.
+/// `ruleset` gives the ruleset with the automatic rules of a step, `ws` starts with the ruleset's own.
+/// The steps that could not be applied are removed, and the applied ones get the label and line they have in `ws`.
+#[cfg(not(feature = "cli"))]
+pub fn replay(
+    ws: &mut Worksheet, steps: &mut Vec<Step>, block_ctx: &BlockContext,
+    ruleset: &dyn Fn(Option<&[String]>) -> RuleSet
+) -> Result<(), ReplayError> {
+    let mut auto_rules = None;
+    for i in 0..steps.len() {
+        if steps[i].auto_rules != auto_rules {
+            auto_rules = steps[i].auto_rules.clone();
+            ws.set_ruleset(ruleset(auto_rules.as_deref()));
+        }
+        match apply_step(ws, &steps[i], block_ctx) {
+            Some(applied) => steps[i] = applied,
+            None => {
+                let label = Some(steps[i].label.clone()).filter(|_| steps[i].label_hash.is_none());
+                steps.truncate(i);
+                return Err(ReplayError { step_index: i, label });
+            }
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(not(feature = "cli"))]
+fn apply_step(ws: &mut Worksheet, step: &Step, block_ctx: &BlockContext) -> Option<Step> {
+    let mut seq = ws.get(step.seq)?;
+    let addresses = symbol_indices_to_addresses(&seq, block_ctx, &step.symbols)?;
+    let possible_actions = seq.get_possible_actions(&addresses);
+    let label = possible_actions.get(step.action).map(|(action, _)| action.to_string())?;
+    if !step.is_same_action(&label) { return None; }
+    
+    let history_len = seq.history.len();
+    seq.try_apply_action_by_index(&addresses, step.action);
+    if seq.history.len() <= history_len { return None; }
+    ws.store(step.seq, seq);
+    return Some(Step { line: history_len, label, label_hash: None, ..step.clone() });
+}
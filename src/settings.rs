@@ -0,0 +1,58 @@
+use dioxus::prelude::*;
+use super::storage;
+
+const ANIMATIONS_STORAGE_KEY: &str = "equaio-animations";
+
+/// User preferences that are not part of the theme
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// animate terms moving between expression lines when an action is applied
+    pub animations: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { animations: true }
+    }
+}
+
+/// Provide the settings signal to the app, initialized from local storage or from the OS `prefers-reduced-motion`
+pub fn use_settings_provider() -> Signal<Settings> {
+    let mut settings = use_context_provider(|| Signal::new(Settings::default()));
+    use_hook(move || spawn(async move {
+        let animations = match storage::load(ANIMATIONS_STORAGE_KEY).await.as_deref() {
+            Some("on") => true,
+            Some("off") => false,
+            _ => {
+                let prefers_reduced_motion = eval(r#"return window.matchMedia("(prefers-reduced-motion: reduce)").matches;"#)
+                    .join().await.ok().and_then(|v| v.as_bool()).unwrap_or(false);
+                !prefers_reduced_motion
+            }
+        };
+        settings.write().animations = animations;
+    }));
+    return settings;
+}
+
+/// Settings of the app, or the default settings if no provider is mounted
+pub fn use_settings() -> Signal<Settings> {
+    let fallback = use_signal(Settings::default);
+    return try_use_context::<Signal<Settings>>().unwrap_or(fallback);
+}
+
+#[component]
+pub fn AnimationToggle() -> Element {
+    let mut settings = use_settings();
+    rsx! {
+        button {
+            class: "navbar-button",
+            title: "Animate transitions between lines",
+            onclick: move |_| {
+                let new_value = !settings.peek().animations;
+                storage::save(ANIMATIONS_STORAGE_KEY, if new_value { "on" } else { "off" });
+                settings.write().animations = new_value;
+            },
+            if settings.read().animations { "animations: on" } else { "animations: off" }
+        }
+    }
+}
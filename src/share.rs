@@ -0,0 +1,222 @@
+//! Links that encode a problem and the steps applied to it in the url fragment, e.g.
+//! `#p=algebra0&s=0.0-2.1.5f3a9c1e,0.3.0.0b7e44a2` or `#d=<base64 worksheet json>&s=...`.
+//! Each step is `seq.symbols.action.label_hash`, with the symbols separated by `-`, followed by
+//! `.auto_rules` with the ids of the automatic rules separated by `+` if they are not the ruleset's own.
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use dioxus::prelude::*;
+use super::content::{self, ContentStore};
+use super::session::{self, SessionData, Step};
+use super::worksheet::WorksheetData;
+use super::{Route, Router};
+
+pub fn to_fragment(session: &SessionData) -> String {
+    let problem = match &session.problem_id {
+        Some(problem_id) => format!("p={}", percent_encode(problem_id)),
+        None => format!("d={}", URL_SAFE_NO_PAD.encode(serde_json::to_string(&session.ws_data).unwrap_or_default())),
+    };
+    let steps = session.steps.iter().map(|step| {
+        let symbols = step.symbols.iter().map(|i| i.to_string()).collect::<Vec<_>>().join("-");
+        let step_str = format!("{}.{}.{}.{:08x}", step.seq, symbols, step.action, session::label_hash(&step.label));
+        match &step.auto_rules {
+            Some(auto_rules) => format!("{}.{}", step_str, auto_rules.join("+")),
+            None => step_str,
+        }
+    }).collect::<Vec<_>>();
+    return format!("{}&s={}", problem, steps.join(","));
+}
+
+/// `value` with every byte other than the unreserved url characters written as `%XX`,
+/// the ids of content pack problems can contain `&` or `=`
+fn percent_encode(value: &str) -> String {
+    value.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+        _ => format!("%{:02X}", byte),
+    }).collect()
+}
+fn percent_decode(value: &str) -> Result<String, String> {
+    let mut bytes = vec![];
+    let mut i = 0;
+    while i < value.len() {
+        if value.as_bytes()[i] != b'%' {
+            bytes.push(value.as_bytes()[i]);
+            i += 1;
+            continue;
+        }
+        let hex = value.get(i + 1..i + 3).filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        let byte = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()).ok_or(format!("invalid escape at {}", i))?;
+        bytes.push(byte);
+        i += 3;
+    }
+    return String::from_utf8(bytes).map_err(|_| "invalid utf-8".to_string());
+}
+
+/// The step encoded as `seq.symbols.action.label_hash[.auto_rules]`, or what is wrong with it
+fn parse_step(step_str: &str) -> Result<Step, String> {
+    let (seq, symbols, action, label_hash, auto_rules) = match step_str.split('.').collect::<Vec<_>>()[..] {
+        [seq, symbols, action, label_hash] => (seq, symbols, action, label_hash, None),
+        [seq, symbols, action, label_hash, auto_rules] => (seq, symbols, action, label_hash, Some(auto_rules)),
+        _ => return Err("expected seq.symbols.action.label_hash[.auto_rules]".to_string()),
+    };
+    let symbols = symbols.split('-').filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| format!("invalid symbol index \"{}\"", s)))
+        .collect::<Result<Vec<usize>, _>>()?;
+    return Ok(Step {
+        seq: seq.parse().map_err(|_| format!("invalid sequence index \"{}\"", seq))?,
+        line: 0,
+        symbols,
+        action: action.parse().map_err(|_| format!("invalid action index \"{}\"", action))?,
+        label: String::new(),
+        label_hash: Some(u32::from_str_radix(label_hash, 16).map_err(|_| format!("invalid label hash \"{}\"", label_hash))?),
+        auto_rules: auto_rules.map(|ids| ids.split('+').filter(|id| !id.is_empty()).map(|id| id.to_string()).collect()),
+    });
+}
+
+/// The worksheet encoded as base64 json in the `d` field
+fn parse_ws_data(value: &str) -> Result<WorksheetData, String> {
+    let json = URL_SAFE_NO_PAD.decode(value).map_err(|err| format!("field \"d\": invalid base64: {}", err))?;
+    return serde_json::from_slice(&json).map_err(|err| format!("field \"d\": invalid worksheet: {}", err));
+}
+
+/// The session encoded in `fragment`, `None` if it does not encode one,
+/// or what is wrong with it, e.g. `step 2 ("0.x.1.5f3a9c1e"): invalid symbol index "x"`
+pub fn from_fragment(fragment: &str, content: &ContentStore) -> Option<Result<SessionData, String>> {
+    let mut problem_id = None;
+    let mut ws_data = None;
+    let mut steps = Ok(vec![]);
+    for (key, value) in fragment.trim_start_matches('#').split('&').filter_map(|pair| pair.split_once('=')) {
+        match key {
+            "p" => problem_id = Some(percent_decode(value).map_err(|err| format!("field \"p\": {}", err))),
+            "d" => ws_data = Some(parse_ws_data(value)),
+            "s" => steps = value.split(',').filter(|s| !s.is_empty()).enumerate()
+                .map(|(i, step_str)| parse_step(step_str).map_err(|err| format!("step {} (\"{}\"): {}", i + 1, step_str, err)))
+                .collect::<Result<Vec<_>, _>>(),
+            _ => {},
+        }
+    }
+    if problem_id.is_none() && ws_data.is_none() { return None; }
+    let steps = match steps {
+        Ok(steps) => steps,
+        Err(err) => return Some(Err(err)),
+    };
+    if let Some(ws_data) = ws_data { return Some(ws_data.map(|ws_data| SessionData { problem_id: None, ws_data, steps })); }
+    let problem_id = match problem_id? {
+        Ok(problem_id) => problem_id,
+        Err(err) => return Some(Err(err)),
+    };
+    return match content.problem(&problem_id) {
+        Some(ws_data) => Some(Ok(SessionData { problem_id: Some(problem_id), ws_data: ws_data.clone(), steps })),
+        None => Some(Err(format!("unknown problem \"{}\"", problem_id))),
+    };
+}
+
+pub async fn read_shared_session(content: &ContentStore) -> Option<Result<SessionData, String>> {
+    let fragment = eval("return window.location.hash;").join().await.ok()?;
+    return from_fragment(fragment.as_str()?, content);
+}
+
+/// Shown instead of the worksheet of a shared link that could not be read
+#[component]
+pub fn SharedLinkErrorPage(router: Router, message: String) -> Element {
+    rsx! {
+        div {
+            class: "navbar",
+            div {
+                class: "navbar-left",
+                button {
+                    class: "navbar-button",
+                    onclick: move |_| router.set(vec![Route::Home]),
+                    "<"
+                }
+            }
+        }
+        div {
+            class: "worksheet-error",
+            "Could not open the shared link: {message}"
+        }
+    }
+}
+
+#[component]
+pub fn ShareButton(problem_id: String, ws_data: WorksheetData, steps: Signal<Vec<Step>>) -> Element {
+    let content = content::use_content();
+    let mut is_copied = use_signal(|| false);
+    rsx! {
+        button {
+            class: "navbar-button",
+            title: "Copy a link to this worksheet",
+            onclick: move |_| {
+                // a problem of the local pack only exists for its author, its worksheet is put in the link instead
+                let problem_id = Some(problem_id.clone()).filter(|id| content.read().is_readonly_problem(id));
+                let session = SessionData { problem_id, ws_data: ws_data.clone(), steps: steps.read().clone() };
+                let fragment = serde_json::to_string(&to_fragment(&session)).unwrap_or_default();
+                let _ = eval(&format!(r#"
+                    const url = window.location.origin + window.location.pathname + "#" + {fragment};
+                    history.replaceState(null, "", url);
+                    navigator.clipboard.writeText(url);
+                "#));
+                is_copied.set(true);
+            },
+            if *is_copied.read() { "link copied" } else { "share" }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(seq: usize, symbols: Vec<usize>, action: usize, label: &str, auto_rules: Option<Vec<String>>) -> Step {
+        Step { seq, line: 0, symbols, action, label: label.to_string(), label_hash: None, auto_rules }
+    }
+
+    #[test]
+    fn fragments_round_trip() {
+        let content = ContentStore::builtin();
+        let ws_data = content.problem("algebra3").unwrap().clone();
+        let steps = vec![
+            step(0, vec![0, 2], 1, "Addition with 0", None),
+            step(1, vec![], 0, "Flip", Some(vec![])),
+            step(0, vec![3], 2, "Multiplication by 1", Some(vec!["add_zero".to_string(), "mul_one".to_string()])),
+        ];
+        let session = SessionData { problem_id: Some("algebra3".to_string()), ws_data: ws_data.clone(), steps: steps.clone() };
+        let fragment = to_fragment(&session);
+        assert!(fragment.starts_with("p=algebra3&s=0.0-2.1."));
+        let parsed = from_fragment(&format!("#{}", fragment), &content).unwrap().unwrap();
+        assert_eq!(parsed.problem_id, session.problem_id);
+        assert_eq!(parsed.ws_data, ws_data);
+        let expected = steps.iter().map(|step| Step { label: String::new(), label_hash: Some(session::label_hash(&step.label)), ..step.clone() });
+        assert_eq!(parsed.steps, expected.collect::<Vec<_>>());
+
+        let session = SessionData { problem_id: None, ..session };
+        let parsed = from_fragment(&to_fragment(&session), &content).unwrap().unwrap();
+        assert_eq!(parsed.problem_id, None);
+        assert_eq!(parsed.ws_data, ws_data);
+    }
+
+    #[test]
+    fn problem_ids_are_percent_encoded() {
+        assert_eq!(percent_encode("pack&problem=1"), "pack%26problem%3D1");
+        assert_eq!(percent_decode("pack%26problem%3D1"), Ok("pack&problem=1".to_string()));
+        assert!(percent_decode("pack%2").is_err());
+        assert!(percent_decode("pack%+1").is_err());
+    }
+
+    #[test]
+    fn reports_malformed_steps() {
+        let content = ContentStore::builtin();
+        let err = from_fragment("#p=algebra0&s=0.0.1.5f3a9c1e,0.x.1.5f3a9c1e", &content).unwrap().unwrap_err();
+        assert_eq!(err, "step 2 (\"0.x.1.5f3a9c1e\"): invalid symbol index \"x\"");
+        let err = from_fragment("#p=algebra0&s=0.1.5f3a9c1e", &content).unwrap().unwrap_err();
+        assert_eq!(err, "step 1 (\"0.1.5f3a9c1e\"): expected seq.symbols.action.label_hash[.auto_rules]");
+        assert!(from_fragment("#d=!!", &content).unwrap().is_err_and(|err| err.starts_with("field \"d\": invalid base64")));
+        assert!(from_fragment("#s=0.0.1.5f3a9c1e", &content).is_none());
+    }
+
+    #[test]
+    fn reports_unknown_problems() {
+        let content = ContentStore::builtin();
+        let err = from_fragment("#p=missing%26problem&s=", &content).unwrap().unwrap_err();
+        assert_eq!(err, "unknown problem \"missing&problem\"");
+    }
+}
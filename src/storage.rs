@@ -0,0 +1,26 @@
+use dioxus::prelude::*;
+
+/// Read a value from the browser local storage
+pub async fn load(key: &str) -> Option<String> {
+    let key = serde_json::to_string(key).ok()?;
+    let value = eval(&format!("return localStorage.getItem({key});")).join().await.ok()?;
+    return value.as_str().map(|s| s.to_string());
+}
+
+/// Offer `contents` as a file download named `filename`
+pub fn download(filename: &str, contents: &str) {
+    let (Ok(filename), Ok(contents)) = (serde_json::to_string(filename), serde_json::to_string(contents)) else { return; };
+    let _ = eval(&format!(r#"
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(new Blob([{contents}], {{ type: "application/json" }}));
+        link.download = {filename};
+        link.click();
+        URL.revokeObjectURL(link.href);
+    "#));
+}
+
+/// Write a value into the browser local storage
+pub fn save(key: &str, value: &str) {
+    let (Ok(key), Ok(value)) = (serde_json::to_string(key), serde_json::to_string(value)) else { return; };
+    let _ = eval(&format!("localStorage.setItem({key}, {value});"));
+}
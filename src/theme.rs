@@ -0,0 +1,167 @@
+use dioxus::prelude::*;
+use super::storage;
+
+const THEME_STORAGE_KEY: &str = "equaio-theme";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeKind {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeKind::Light => "light",
+            ThemeKind::Dark => "dark",
+            ThemeKind::HighContrast => "high_contrast",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "light" => Some(ThemeKind::Light),
+            "dark" => Some(ThemeKind::Dark),
+            "high_contrast" => Some(ThemeKind::HighContrast),
+            _ => None,
+        }
+    }
+    pub fn next(&self) -> Self {
+        match self {
+            ThemeKind::Light => ThemeKind::Dark,
+            ThemeKind::Dark => ThemeKind::HighContrast,
+            ThemeKind::HighContrast => ThemeKind::Light,
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemeKind::Light => "light",
+            ThemeKind::Dark => "dark",
+            ThemeKind::HighContrast => "contrast",
+        }
+    }
+    pub fn theme(&self) -> Theme {
+        match self {
+            ThemeKind::Light => Theme {
+                background: "#FFF8F6",
+                text: "#000000",
+                muted_text: "#808080",
+                primary: "#EF7B45",
+                secondary: "#FFDBCE",
+                secondary_hover: "#EECCBF",
+                hover_overlay: "rgba(0,0,0,0.1)",
+                row_hover: "rgba(0,0,0,0.05)",
+                action_background: "rgba(0,0,0,0.1)",
+                action_background_hover: "rgba(0,0,0,0.2)",
+            },
+            ThemeKind::Dark => Theme {
+                background: "#1E1A19",
+                text: "#F2E9E6",
+                muted_text: "#A09894",
+                primary: "#EF7B45",
+                secondary: "#5A3A2E",
+                secondary_hover: "#6B463A",
+                hover_overlay: "rgba(255,255,255,0.1)",
+                row_hover: "rgba(255,255,255,0.05)",
+                action_background: "rgba(255,255,255,0.08)",
+                action_background_hover: "rgba(255,255,255,0.16)",
+            },
+            ThemeKind::HighContrast => Theme {
+                background: "#FFFFFF",
+                text: "#000000",
+                muted_text: "#000000",
+                primary: "#000000",
+                secondary: "#FFE600",
+                secondary_hover: "#FFC800",
+                hover_overlay: "rgba(0,0,0,0.2)",
+                row_hover: "rgba(0,0,0,0.1)",
+                action_background: "#E0E0E0",
+                action_background_hover: "#BDBDBD",
+            },
+        }
+    }
+}
+
+/// Colors of the app, exposed to the stylesheets as css custom properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub background: &'static str,
+    pub text: &'static str,
+    pub muted_text: &'static str,
+    pub primary: &'static str,
+    pub secondary: &'static str,
+    pub secondary_hover: &'static str,
+    pub hover_overlay: &'static str,
+    pub row_hover: &'static str,
+    pub action_background: &'static str,
+    pub action_background_hover: &'static str,
+}
+
+impl Theme {
+    fn css_variables(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("--background-color", self.background),
+            ("--text-color", self.text),
+            ("--muted-text-color", self.muted_text),
+            ("--primary-color", self.primary),
+            ("--secondary-color", self.secondary),
+            ("--secondary-color-hover", self.secondary_hover),
+            ("--hover-overlay-color", self.hover_overlay),
+            ("--row-hover-color", self.row_hover),
+            ("--action-background-color", self.action_background),
+            ("--action-background-color-hover", self.action_background_hover),
+        ]
+    }
+    fn root_rule(&self) -> String {
+        let declarations = self.css_variables().iter()
+            .map(|(name, value)| format!("{name}: {value};"))
+            .collect::<Vec<_>>().join(" ");
+        format!(":root {{ {declarations} }}")
+    }
+}
+
+/// Provide the theme signal to the app, initialized from local storage or from the OS `prefers-color-scheme`
+pub fn use_theme_provider() -> Signal<ThemeKind> {
+    let mut theme = use_context_provider(|| Signal::new(ThemeKind::default()));
+    use_hook(move || spawn(async move {
+        let stored = storage::load(THEME_STORAGE_KEY).await.and_then(|name| ThemeKind::from_name(&name));
+        let kind = match stored {
+            Some(kind) => kind,
+            None => {
+                let prefers_dark = eval(r#"return window.matchMedia("(prefers-color-scheme: dark)").matches;"#)
+                    .join().await.ok().and_then(|v| v.as_bool()).unwrap_or(false);
+                if prefers_dark { ThemeKind::Dark } else { ThemeKind::Light }
+            }
+        };
+        theme.set(kind);
+    }));
+    return theme;
+}
+
+/// The css custom properties of the current theme, the only place the colors are defined
+#[component]
+pub fn ThemeStyle() -> Element {
+    let theme = use_context::<Signal<ThemeKind>>();
+    let rule = theme.read().theme().root_rule();
+    rsx! {
+        style { "{rule}" }
+    }
+}
+
+#[component]
+pub fn ThemeToggle() -> Element {
+    let mut theme = use_context::<Signal<ThemeKind>>();
+    rsx! {
+        button {
+            class: "navbar-button",
+            title: "Change color scheme",
+            onclick: move |_| {
+                let new_value = theme.peek().next();
+                storage::save(THEME_STORAGE_KEY, new_value.name());
+                theme.set(new_value);
+            },
+            "theme: {theme.read().label()}"
+        }
+    }
+}
@@ -1,19 +1,96 @@
-pub fn convert_mathvar(original: String) -> String {
-    original.chars().map(|c| to_mathvar(c).unwrap_or(c)).collect()
+/// A piece of math text, already converted to the characters it is displayed with
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathToken {
+    pub text: String,
+    pub subscript: Option<String>,
+    pub is_variable: bool,
+}
+impl MathToken {
+    pub fn plain(text: &str) -> Self {
+        MathToken { text: text.to_string(), subscript: None, is_variable: false }
+    }
+}
+
+/// Split `original` into identifiers and everything in between.
+/// Identifiers in `variables` are rendered in math italic, other identifiers (constants, function names) stay upright.
+/// Greek letter names (e.g. `alpha`, `Theta`) are converted into the letter itself, and `x_1` gets `1` as its subscript.
+pub fn tokenize_math(original: &str, variables: &[String]) -> Vec<MathToken> {
+    let chars = original.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            plain.push(if chars[i] == '-' { '\u{2212}' } else { chars[i] });
+            i += 1;
+            continue;
+        }
+        
+        let start = i;
+        while i < chars.len() && chars[i].is_alphanumeric() { i += 1; }
+        let name = chars[start..i].iter().collect::<String>();
+        let mut subscript = None;
+        if i + 1 < chars.len() && chars[i] == '_' && chars[i+1].is_alphanumeric() {
+            let sub_start = i + 1;
+            i = sub_start;
+            while i < chars.len() && chars[i].is_alphanumeric() { i += 1; }
+            subscript = Some(chars[sub_start..i].iter().collect::<String>());
+        }
+        
+        if !plain.is_empty() {
+            tokens.push(MathToken { text: std::mem::take(&mut plain), subscript: None, is_variable: false });
+        }
+        let full_name = match &subscript {
+            Some(sub) => format!("{}_{}", name, sub),
+            None => name.clone(),
+        };
+        let is_variable = variables.contains(&full_name) || variables.contains(&name);
+        let text = match greek_letter(&name) {
+            Some(c) => c.to_string(),
+            None => name,
+        };
+        let (text, subscript) = if is_variable {
+            (to_math_italic(&text), subscript.map(|s| to_math_italic(&s)))
+        } else {
+            (text, subscript)
+        };
+        tokens.push(MathToken { text, subscript, is_variable });
+    }
+    if !plain.is_empty() {
+        tokens.push(MathToken { text: plain, subscript: None, is_variable: false });
+    }
+    return tokens;
+}
+
+const GREEK_LETTERS: [(&str, char); 24] = [
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ε'), ("zeta", 'ζ'),
+    ("eta", 'η'), ("theta", 'θ'), ("iota", 'ι'), ("kappa", 'κ'), ("lambda", 'λ'), ("mu", 'μ'),
+    ("nu", 'ν'), ("xi", 'ξ'), ("omicron", 'ο'), ("pi", 'π'), ("rho", 'ρ'), ("sigma", 'σ'),
+    ("tau", 'τ'), ("upsilon", 'υ'), ("phi", 'φ'), ("chi", 'χ'), ("psi", 'ψ'), ("omega", 'ω'),
+];
+
+/// `alpha` -> `α`, `Alpha` -> `Α`
+fn greek_letter(name: &str) -> Option<char> {
+    GREEK_LETTERS.iter().find_map(|(greek_name, c)| {
+        if *greek_name == name { return Some(*c); }
+        let mut capitalized = greek_name.chars();
+        let first = capitalized.next()?.to_ascii_uppercase();
+        if format!("{}{}", first, capitalized.as_str()) == name { return c.to_uppercase().next(); }
+        return None;
+    })
 }
 
-#[allow(dead_code)]
-pub fn convert_mathvar_vec(original: String, variables: Vec<String>) -> String {
-    original.chars().map(|c| {
-        if variables.contains(&c.to_string()) { to_mathvar(c).unwrap_or(c) } else { c }
-    }).collect()
+fn to_math_italic(original: &str) -> String {
+    original.chars().map(|c| to_mathvar(c).unwrap_or(c)).collect()
 }
 
 fn to_mathvar(c: char) -> Option<char> {
     match c {
+        'h' => Some('\u{210E}'), // planck constant, the math italic block has a hole here
         'a'..='z' => std::char::from_u32(c as u32 + 0x1D44E - 'a' as u32),
         'A'..='Z' => std::char::from_u32(c as u32 + 0x1D434 - 'A' as u32),
-        '-' => std::char::from_u32(0x2212),
+        'α'..='ω' => std::char::from_u32(c as u32 + 0x1D6FC - 'α' as u32),
+        'Α'..='Ω' => std::char::from_u32(c as u32 + 0x1D6E2 - 'Α' as u32),
         _ => Some(c), 
     }
-}
\ No newline at end of file
+}
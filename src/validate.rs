@@ -0,0 +1,125 @@
+//! Checks of a ruleset json that point at the offending rule and field.
+//! Shared with the build script, which rejects invalid bundled rulesets.
+use serde_json::{json, Value};
+
+/// A problem found in a ruleset, e.g. `ruleset "algebra", rule "add_zero", field "expr": ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulesetError {
+    pub ruleset: String,
+    pub rule_id: Option<String>,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for RulesetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ruleset \"{}\"", self.ruleset)?;
+        if let Some(rule_id) = &self.rule_id { write!(f, ", rule \"{}\"", rule_id)?; }
+        if let Some(field) = &self.field { write!(f, ", field \"{}\"", field)?; }
+        write!(f, ": {}", self.message)
+    }
+}
+
+fn try_parse(value: &Value) -> Result<(), String> {
+    return equaio::rule::parse_ruleset_from_json(&value.to_string())
+        .map(|_| ())
+        .map_err(|err| format!("{:?}", err));
+}
+
+const FUNCTION_STYLES: [&str; 3] = ["radical", "abs", "named"];
+
+/// Problems of the `presentation` section as `(field, message)`, it is read by the app and not by the parser
+fn validate_presentation(presentation: &Value) -> Vec<(String, String)> {
+    let Some(fields) = presentation.as_object() else {
+        return vec![("presentation".to_string(), "expected an object".to_string())];
+    };
+    let is_string_list = |value: &Value| value.as_array().is_some_and(|items| items.iter().all(|item| item.is_string()));
+    let is_pair = |value: &Value| is_string_list(value) && value.as_array().is_some_and(|pair| pair.len() == 2);
+    let is_function_style = |value: &Value| value.as_str().is_some_and(|style| FUNCTION_STYLES.contains(&style));
+    let mut errors = vec![];
+    for (key, value) in fields {
+        let (is_valid, expected) = match key.as_str() {
+            "fraction_ops" | "conceal_ops" | "op_precedence" | "base_functions" =>
+                (is_string_list(value), "an array of operators"),
+            "inverse_ops" =>
+                (value.as_array().is_some_and(|pairs| pairs.iter().all(is_pair)), "an array of [operator, inverse] pairs"),
+            "symbols" =>
+                (value.as_object().is_some_and(|symbols| symbols.values().all(|display| display.is_string())), "an object of symbol to displayed text"),
+            "functions" =>
+                (value.as_object().is_some_and(|functions| functions.values().all(is_function_style)), "an object of operator to \"radical\", \"abs\" or \"named\""),
+            _ => { errors.push((format!("presentation.{}", key), "unknown field".to_string())); continue; },
+        };
+        if !is_valid { errors.push((format!("presentation.{}", key), format!("expected {}", expected))); }
+    }
+    return errors;
+}
+
+/// Every problem of the ruleset json `rulestr`, empty if it parses
+pub fn validate_ruleset(name: &str, rulestr: &str) -> Vec<RulesetError> {
+    let error = |rule_id: Option<&str>, field: Option<&str>, message: String| RulesetError {
+        ruleset: name.to_string(),
+        rule_id: rule_id.map(|id| id.to_string()),
+        field: field.map(|field| field.to_string()),
+        message,
+    };
+    let value: Value = match serde_json::from_str(rulestr) {
+        Ok(value) => value,
+        Err(err) => return vec![error(None, None, format!("invalid json: {}", err))],
+    };
+    let Some(rules) = value.get("rules").and_then(|rules| rules.as_array()) else {
+        return vec![error(None, Some("rules"), "expected an array of rules".to_string())];
+    };
+
+    let mut errors = vec![];
+    if let Some(presentation) = value.get("presentation") {
+        errors.extend(validate_presentation(presentation).into_iter().map(|(field, message)| error(None, Some(&field), message)));
+    }
+    // the rules are checked one at a time, against the context and variations of the whole ruleset
+    let mut base = value.clone();
+    base["rules"] = json!([]);
+    if let Err(err) = try_parse(&base) {
+        errors.push(error(None, Some("variations"), err));
+        base["variations"] = json!([]);
+    }
+    let mut ids: Vec<&str> = vec![];
+    for (i, rule) in rules.iter().enumerate() {
+        let index_label = format!("#{}", i);
+        let rule_id = rule.get("id").and_then(|id| id.as_str());
+        let rule_name = rule_id.unwrap_or(&index_label);
+        let mut is_complete = true;
+        for field in ["id", "expr", "label"] {
+            if !rule.get(field).is_some_and(|value| value.is_string()) {
+                errors.push(error(Some(rule_name), Some(field), "expected a string".to_string()));
+                is_complete = false;
+            }
+        }
+        if rule.get("auto").is_some_and(|auto| !auto.is_boolean()) {
+            errors.push(error(Some(rule_name), Some("auto"), "expected true or false".to_string()));
+        }
+        if rule.get("examples").is_some_and(|examples| !examples.is_array()) {
+            errors.push(error(Some(rule_name), Some("examples"), "expected an array of examples".to_string()));
+        }
+        if let Some(id) = rule_id {
+            if ids.contains(&id) { errors.push(error(Some(id), Some("id"), "duplicated rule id".to_string())); }
+            ids.push(id);
+        }
+        if !is_complete { continue; }
+
+        let mut without_variations = rule.clone();
+        if let Some(rule) = without_variations.as_object_mut() { rule.remove("variations"); }
+        let mut single = base.clone();
+        single["rules"] = json!([without_variations]);
+        if let Err(err) = try_parse(&single) {
+            errors.push(error(Some(rule_name), Some("expr"), err));
+            continue;
+        }
+        single["rules"] = json!([rule]);
+        if let Err(err) = try_parse(&single) {
+            errors.push(error(Some(rule_name), Some("variations"), err));
+        }
+    }
+    if errors.is_empty() {
+        if let Err(err) = try_parse(&value) { errors.push(error(None, None, err)); }
+    }
+    return errors;
+}
@@ -1,40 +1,99 @@
-use super::json;
-use super::utils;
+use super::content::ContentStore;
+use super::presentation::Presentation;
+use serde::{Deserialize, Serialize};
+// the components, left out of the cli
+#[cfg(not(feature = "cli"))]
+use super::content;
+#[cfg(not(feature = "cli"))]
+use super::utils::{self, MathToken};
+#[cfg(not(feature = "cli"))]
+use super::presentation::FunctionStyle;
+#[cfg(not(feature = "cli"))]
+use super::settings;
+#[cfg(not(feature = "cli"))]
+use super::justification;
+#[cfg(not(feature = "cli"))]
+use super::rule_match;
+#[cfg(not(feature = "cli"))]
+use super::session::{self, Step};
+#[cfg(not(feature = "cli"))]
+use std::cell::Cell;
+#[cfg(not(feature = "cli"))]
 use std::collections::HashMap;
+#[cfg(not(feature = "cli"))]
+use std::rc::Rc;
+#[cfg(not(feature = "cli"))]
 use dioxus::prelude::*;
+#[cfg(not(feature = "cli"))]
+use dioxus_logger::tracing::warn;
+#[cfg(not(feature = "cli"))]
 use equaio::block::Block;
+#[cfg(not(feature = "cli"))]
 use equaio::expression::Address;
-use equaio::{pair_map, vec_strings, vec_index_map};
-use serde::{Deserialize, Serialize};
 
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct WorksheetData {
     pub label: String,
     pub sublabel: Option<String>,
+    /// instructions or explanations in Markdown, with math between `$`
+    #[serde(default)]
+    pub description: Option<String>,
     pub rule: String,
     pub variables: Vec<String>,
     pub initial_expressions: Vec<String>,
+    /// expected final expressions, shown in the solution key
+    #[serde(default)]
+    pub solution: Vec<String>,
+    /// ids of the only rules that can be used, every rule if empty
+    #[serde(default)]
+    pub allowed_rules: Vec<String>,
+    /// ids of the rules that cannot be used
+    #[serde(default)]
+    pub forbidden_rules: Vec<String>,
+    #[serde(default)]
+    pub difficulty: Option<Difficulty>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// estimated time to solve the problem, in minutes
+    #[serde(default)]
+    pub estimated_minutes: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
 }
 
-fn get_ruleset(rulename: String) -> equaio::rule::RuleSet {
-    let rulestr = match rulename.as_str() {
-        "algebra" => json::ALGEBRA_RULES,
-        "algebra_simplify" => json::ALGEBRA_SIMPLIFY_RULES,
-        _ => json::ALGEBRA_RULES // TODO: change the default
-    };
-    let ruleset = equaio::rule::parse_ruleset_from_json(rulestr);
-    return ruleset.unwrap();
+#[cfg(not(feature = "cli"))]
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        return Self::ALL.into_iter().find(|difficulty| difficulty.name() == name);
+    }
 }
-fn init_worksheet(ws_data: WorksheetData) -> equaio::worksheet::Worksheet {
-    let ruleset = get_ruleset(ws_data.rule);
+
+fn new_worksheet(ruleset: equaio::rule::RuleSet) -> equaio::worksheet::Worksheet {
     let mut ws = equaio::worksheet::Worksheet::new();
     ws.set_ruleset(ruleset);
     // TODO: load general normalization and possible actions functions
     ws.set_normalization_function(|expr,ctx| expr.normalize_algebra(ctx));
     ws.set_get_possible_actions_function(|expr,ctx,addr_vec| 
         equaio::algebra::get_possible_actions::algebra(expr,ctx,addr_vec));
-    
+    return ws;
+}
+pub fn init_worksheet(content: &ContentStore, ws_data: WorksheetData) -> equaio::worksheet::Worksheet {
+    let mut ws = new_worksheet(content.restricted_ruleset(&ws_data, None));
     let ctx = ws.get_expression_context().add_params(ws_data.variables);
     for expr_str in ws_data.initial_expressions {
         let expr = equaio::parser::parser::to_expression(expr_str, &ctx);
@@ -43,16 +102,86 @@ fn init_worksheet(ws_data: WorksheetData) -> equaio::worksheet::Worksheet {
     return ws;
 }
 
+/// Parse `expr_strs` (e.g. the solution of a problem) with the ruleset and variables of `ws_data`
+#[cfg(not(feature = "cli"))]
+pub fn parse_expressions(content: &ContentStore, ws_data: &WorksheetData, expr_strs: &[String]) -> Vec<equaio::expression::Expression> {
+    let ws = new_worksheet(content.ruleset(&ws_data.rule));
+    let ctx = ws.get_expression_context().add_params(ws_data.variables.clone());
+    return expr_strs.iter()
+        .filter_map(|expr_str| equaio::parser::parser::to_expression(expr_str.clone(), &ctx))
+        .collect();
+}
+
+/// Whether every expression of `solution` is the last line of one of the expression sequences
+#[cfg(not(feature = "cli"))]
+pub fn is_solved(ws: &equaio::worksheet::Worksheet, solution: &[equaio::expression::Expression]) -> bool {
+    if solution.is_empty() { return false; }
+    let last_exprs = (0..ws.len())
+        .filter_map(|i| ws.get(i))
+        .filter_map(|seq| seq.history.last().map(|line| line.expr.clone()))
+        .collect::<Vec<_>>();
+    return solution.iter().all(|expr| last_exprs.contains(expr));
+}
+
+/// Provide the presentation of the ruleset of `ws_data` to the blocks rendered below the calling component
+#[cfg(not(feature = "cli"))]
+pub fn use_presentation_provider(ws_data: &WorksheetData) -> Presentation {
+    let content = content::use_content();
+    return use_context_provider(|| get_presentation(&content.peek(), ws_data));
+}
+
+pub fn get_presentation(content: &ContentStore, ws_data: &WorksheetData) -> Presentation {
+    return content.presentation(&ws_data.rule).with_variables(ws_data.variables.clone());
+}
+
+#[cfg(not(feature = "cli"))]
 #[component]
-pub fn Worksheet(ws_data: WorksheetData) -> Element {
-    let ws = use_signal(|| init_worksheet(ws_data));
+pub fn Worksheet(
+    ws_data: WorksheetData, 
+    #[props(default)] print_mode: bool,
+    steps: Option<Signal<Vec<Step>>>,
+    /// called when the last lines match the solution of the problem
+    on_solved: Option<EventHandler<()>>
+) -> Element {
+    let content = content::use_content();
+    let block_ctx = use_presentation_provider(&ws_data).block_context();
+    use_context_provider(|| ws_data.clone());
+    let solution = use_hook(|| parse_expressions(&content.peek(), &ws_data, &ws_data.solution));
+    let fallback_steps = use_signal(Vec::<Step>::new);
+    let mut steps = steps.unwrap_or(fallback_steps);
+    let (ws, replay_error) = use_hook(|| {
+        let mut ws = init_worksheet(&content.peek(), ws_data.clone());
+        let ruleset = |auto_rules: Option<&[String]>| content.peek().restricted_ruleset(&ws_data, auto_rules);
+        let replay_error = session::replay(&mut ws, &mut steps.write(), &block_ctx, &ruleset).err();
+        if let Some(err) = &replay_error { warn!("{}", err); }
+        (Signal::new(ws), replay_error)
+    });
+    // the automatic rules the next step is applied with, as recorded in its `auto_rules`
+    let auto_rules = use_signal(|| steps.peek().last().and_then(|step| step.auto_rules.clone()));
+    let was_solved = use_hook(|| Rc::new(Cell::new(false)));
+    use_effect(move || {
+        let solved = is_solved(&ws.read(), &solution);
+        if solved && !was_solved.get() {
+            if let Some(on_solved) = on_solved { on_solved.call(()); }
+        }
+        was_solved.set(solved);
+    });
     
     rsx! {
+        if let Some(err) = replay_error {
+            div {
+                class: "worksheet-error",
+                "Could not restore the whole worksheet: {err}"
+            }
+        }
+        if !print_mode {
+            AutoRulesPanel { ws_data, ws, active_auto_rules: auto_rules }
+        }
         div {
-            class: "worksheet",
+            class: if print_mode { "worksheet print" } else { "worksheet" },
             for i in 0..ws.read().len() {
                 if let Some(seq) = ws.read().get(i) {
-                    ExpressionSequence { seq, seq_index: 0, ws }
+                    ExpressionSequence { seq, seq_index: i, ws, steps, auto_rules, print_mode }
                 }
             }
         }
@@ -60,11 +189,86 @@ pub fn Worksheet(ws_data: WorksheetData) -> Element {
     
 }
 
+/// Switch the automatic rules of the worksheet on or off, or pick them one by one among the rules marked `auto` in its ruleset.
+/// The new rules apply to the next actions, the lines already in the worksheet are kept.
+#[cfg(not(feature = "cli"))]
+#[component]
+fn AutoRulesPanel(
+    ws_data: WorksheetData, ws: Signal<equaio::worksheet::Worksheet>,
+    /// the ids of the automatic rules, `None` for the ruleset's own
+    active_auto_rules: Signal<Option<Vec<String>>>
+) -> Element {
+    let content = content::use_content();
+    // only the simplification rules, e.g. a rule and its inverse applied automatically would loop
+    let rules = content.read().available_rules(&ws_data).into_iter().filter(|rule| rule.auto).collect::<Vec<_>>();
+    let default_rules = rules.iter().map(|rule| rule.id.clone()).collect::<Vec<_>>();
+    let mut is_enabled = use_signal(|| active_auto_rules.peek().as_ref().is_none_or(|ids| !ids.is_empty()));
+    let mut auto_rules = use_signal(|| match active_auto_rules.peek().clone() {
+        Some(ids) if !ids.is_empty() => ids,
+        _ => default_rules.clone(),
+    });
+    use_effect({
+        let ws_data = ws_data.clone();
+        let mut active_auto_rules = active_auto_rules;
+        move || {
+            let auto_rules = if *is_enabled.read() { auto_rules.read().clone() } else { vec![] };
+            let is_default = auto_rules.len() == default_rules.len() && auto_rules.iter().all(|id| default_rules.contains(id));
+            let auto_rules = Some(auto_rules).filter(|_| !is_default);
+            let ruleset = content.peek().restricted_ruleset(&ws_data, auto_rules.as_deref());
+            ws.write().set_ruleset(ruleset);
+            active_auto_rules.set(auto_rules);
+        }
+    });
+    if rules.is_empty() { return rsx! {}; }
+    
+    rsx! {
+        details {
+            class: "rules-panel",
+            summary {
+                "Automatic simplification: "
+                if *is_enabled.read() { "on" } else { "off" }
+            }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: *is_enabled.read(),
+                    oninput: move |e| is_enabled.set(e.value() == "true")
+                }
+                "apply rules automatically"
+            }
+            ul {
+                class: "auto-rules-list",
+                for rule in rules {
+                    li {
+                        label {
+                            input {
+                                r#type: "checkbox",
+                                checked: auto_rules.read().contains(&rule.id),
+                                disabled: !*is_enabled.read(),
+                                oninput: {
+                                    let id = rule.id.clone();
+                                    move |e: FormEvent| {
+                                        if e.value() == "true" { auto_rules.write().push(id.clone()); }
+                                        else { auto_rules.write().retain(|rule_id| rule_id != &id); }
+                                    }
+                                }
+                            }
+                            "{rule.label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
 #[derive(PartialEq, Clone)]
 struct GroupedHistory {
     pub history: Vec<equaio::worksheet::ExpressionLine>,
     pub line_index: usize, // index of the first line in the history
 }
+#[cfg(not(feature = "cli"))]
 impl GroupedHistory {
     pub fn new(history: Vec<equaio::worksheet::ExpressionLine>, line_index: usize) -> Self {
         GroupedHistory { history, line_index }
@@ -78,6 +282,7 @@ impl GroupedHistory {
     }
 }
 
+#[cfg(not(feature = "cli"))]
 fn group_auto_history(history: Vec<equaio::worksheet::ExpressionLine>) -> Vec<GroupedHistory> {
     let mut history_grouped = vec![];
     let mut current_group = vec![];
@@ -96,25 +301,43 @@ fn group_auto_history(history: Vec<equaio::worksheet::ExpressionLine>) -> Vec<Gr
     return history_grouped;
 }
 
+#[cfg(not(feature = "cli"))]
 #[component]
 pub fn ExpressionSequence(
     seq: equaio::worksheet::WorkableExpressionSequence,  
-    seq_index: usize,  ws: Signal<equaio::worksheet::Worksheet>
+    seq_index: usize,  ws: Signal<equaio::worksheet::Worksheet>,
+    steps: Signal<Vec<Step>>,
+    /// the automatic rules recorded with the applied steps
+    auto_rules: Signal<Option<Vec<String>>>,
+    #[props(default)] print_mode: bool
 )  -> Element 
 {
-    //TODO: load from json
-    let block_ctx = equaio::block::BlockContext {
-        inverse_ops: pair_map![("+", "-"), ("*", "/")],
-        fraction_ops: vec_strings!["/"],
-        conceal_ops: vec_strings!["*"],
-        op_precedence: vec_index_map!["-", "+", "/", "*"]
-    };
+    let block_ctx = use_context::<Presentation>().block_context();
     
     let grouped_history = group_auto_history(seq.history.clone());
     let last_index = grouped_history.len() - 1;
     
     let mut active_address = use_signal(|| Vec::<Address>::new());
+    let settings = settings::use_settings();
+    let content = content::use_content();
+    let ws_data = use_context::<WorksheetData>();
+    let last_history_len = use_hook(|| Rc::new(Cell::new(seq.history.len())));
+    use_effect({
+        let block_ctx = block_ctx.clone();
+        move || {
+            let Some(seq) = ws.read().get(seq_index) else { return; };
+            let previous_len = last_history_len.replace(seq.history.len());
+            if seq.history.len() <= previous_len || !settings.peek().animations { return; }
+            let step = steps.peek().iter().rev().find(|step| step.seq == seq_index && step.line >= previous_len).cloned();
+            let pairs = transition_pairs(&content.peek(), &ws_data, &block_ctx, &seq, step.as_ref()).iter()
+                .map(|(from, to)| (address_key(from), address_key(to)))
+                .collect::<Vec<_>>();
+            let pairs = serde_json::to_string(&pairs).unwrap_or("[]".to_string());
+            let _ = eval(&LINE_TRANSITION_SCRIPT.replace("{seq_index}", &seq_index.to_string()).replace("{pairs}", &pairs));
+        }
+    });
     let possible_actions = seq.get_possible_actions(&active_address.read());
+    let selected_symbols = session::selected_symbols(&seq, &block_ctx, &active_address.read());
     
     let possible_actions_presentable = possible_actions.iter().enumerate()
         .map(|(i,(action, expr))| (i, action.to_string(), Block::from_root_expression(expr, &block_ctx)))
@@ -132,6 +355,7 @@ pub fn ExpressionSequence(
     
     rsx!( div {
         class: "expression-sequence-container",
+        id: "expression-sequence-{seq_index}",
         div {
             class: "expression-sequence-history-container",
             for (i, group) in grouped_history.iter().enumerate() {
@@ -141,30 +365,120 @@ pub fn ExpressionSequence(
                         is_first: i == 0, is_last: i == last_index,
                         active_address,
                         block_ctx: block_ctx.clone(),
-                        address_update_handler, ws, seq_index
+                        address_update_handler, ws, steps, seq_index, print_mode
                     }
                 }
             }
         }
-        div {
-            class: "possible-actions-container",
-            for (i, action, block) in possible_actions_presentable {
-                div {
-                    class: "possible-action-button",
-                    onclick: move |_| {
-                        let mut seq = ws.write().get(seq_index).unwrap();
-                        seq.try_apply_action_by_index(&active_address.read(), i);
-                        ws.write().store(seq_index, seq);
-                        active_address.write().clear();
-                    },
-                    span { class:"possible-action-caption" , "{action}" }
-                    Block { block, active_address: None, on_address_update: |_| {} }
+        if !print_mode {
+            div {
+                class: "possible-actions-container",
+                for (i, action, block) in possible_actions_presentable {
+                    div {
+                        class: "possible-action-button",
+                        onclick: {
+                            let step = Step { 
+                                seq: seq_index, line: seq.history.len(), 
+                                symbols: selected_symbols.clone(), action: i, label: action.clone(), label_hash: None,
+                                auto_rules: None
+                            };
+                            move |_| {
+                                let mut seq = ws.write().get(seq_index).unwrap();
+                                let history_len = seq.history.len();
+                                seq.try_apply_action_by_index(&active_address.read(), i);
+                                if seq.history.len() > history_len {
+                                    steps.write().push(Step { auto_rules: auto_rules.peek().clone(), ..step.clone() });
+                                }
+                                ws.write().store(seq_index, seq);
+                                active_address.write().clear();
+                            }
+                        },
+                        span { class:"possible-action-caption" , "{action}" }
+                        Block { block, active_address: None, on_address_update: |_| {} }
+                    }
                 }
             }
         }
     })
 }
 
+/// Addresses of the symbols of the line a step was applied to, paired with the address of the same term
+/// in the last line, following the rule of the step. Symbols without a known counterpart are not paired.
+#[cfg(not(feature = "cli"))]
+fn transition_pairs(
+    content: &ContentStore, ws_data: &WorksheetData, block_ctx: &equaio::block::BlockContext,
+    seq: &equaio::worksheet::WorkableExpressionSequence, step: Option<&Step>
+) -> Vec<(Address, Address)> {
+    let line_block = |i: usize| seq.history.get(i).map(|line| Block::from_root_expression(&line.expr, block_ctx));
+    let Some(step) = step else { return vec![]; };
+    let previous = step.line.checked_sub(1).and_then(line_block);
+    let last = seq.history.len().checked_sub(1).and_then(line_block);
+    let (Some(previous), Some(produced), Some(last)) = (previous, line_block(step.line), last) else { return vec![]; };
+    let Some(rule_match) = rule_match::match_step(content, ws_data, block_ctx, &step.label, &previous, step) else { return vec![]; };
+    // the automatic rules applied after the step keep the symbols they do not touch in place
+    let unchanged = rule_match::unchanged_symbols(&produced, &last);
+    return rule_match.symbol_pairs(&previous, &produced).into_iter()
+        .filter(|(_, addr)| unchanged.iter().any(|(kept, _)| kept == addr))
+        .collect();
+}
+
+/// The `data-address` of a rendered symbol
+#[cfg(not(feature = "cli"))]
+fn address_key(address: &Address) -> String {
+    format!("{:?}", address)
+}
+
+/// Animate the symbols of the newest line from their position in the previous line.
+/// `{pairs}` are the `[previous, new]` addresses of the same terms, the other symbols fade in or out.
+#[cfg(not(feature = "cli"))]
+const LINE_TRANSITION_SCRIPT: &str = r#"
+const container = document.getElementById("expression-sequence-{seq_index}");
+if (!container) { return; }
+const pairs = {pairs};
+requestAnimationFrame(() => {
+    const lines = container.querySelectorAll(".expression-line-container");
+    if (lines.length < 2) { return; }
+    const symbols = (line) => new Map(Array.from(line.querySelectorAll(".expression-line-block .block-symbol[data-address]"))
+        .filter((el) => el.offsetParent !== null)
+        .map((el) => [el.dataset.address, el]));
+    const previous = symbols(lines[lines.length - 2]);
+    const current = symbols(lines[lines.length - 1]);
+    const currentTop = lines[lines.length - 1].getBoundingClientRect().top;
+    const paired = new Set();
+    const moved = new Set();
+    for (const [from, to] of pairs) {
+        const fromEl = previous.get(from);
+        const toEl = current.get(to);
+        if (!fromEl || !toEl || moved.has(to)) { continue; }
+        paired.add(from);
+        moved.add(to);
+        const fromRect = fromEl.getBoundingClientRect();
+        const toRect = toEl.getBoundingClientRect();
+        toEl.animate([
+            { transform: `translate(${fromRect.left - toRect.left}px, ${fromRect.top - toRect.top}px)` },
+            { transform: "none" },
+        ], { duration: 500, easing: "ease-in-out" });
+    }
+    current.forEach((el, address) => {
+        if (moved.has(address)) { return; }
+        el.animate([{ opacity: 0 }, { opacity: 1 }], { duration: 500, easing: "ease-out" });
+    });
+    previous.forEach((el, address) => {
+        if (paired.has(address)) { return; }
+        const rect = el.getBoundingClientRect();
+        const ghost = el.cloneNode(true);
+        ghost.classList.add("expression-transition-ghost");
+        Object.assign(ghost.style, { left: `${rect.left}px`, top: `${rect.top}px` });
+        document.body.appendChild(ghost);
+        ghost.animate([
+            { opacity: 1, transform: "none" },
+            { opacity: 0, transform: `translateY(${currentTop - rect.top}px) scale(0.5)` },
+        ], { duration: 500, easing: "ease-in" }).onfinish = () => ghost.remove();
+    });
+});
+"#;
+
+#[cfg(not(feature = "cli"))]
 #[component]
 fn GroupedHistoryBlock(
     group: GroupedHistory, 
@@ -173,10 +487,14 @@ fn GroupedHistoryBlock(
     block_ctx: equaio::block::BlockContext,
     address_update_handler: EventHandler<(Address, bool)>,
     seq_index: usize,
-    ws: Signal<equaio::worksheet::Worksheet>
+    ws: Signal<equaio::worksheet::Worksheet>,
+    steps: Signal<Vec<Step>>,
+    print_mode: bool
 ) -> Element 
 {
+    // the print layout shows the full history
     let is_expanded = use_signal(|| false);
+    let is_expanded_view = *is_expanded.read() || print_mode;
     let group_data = group.to_block_data(&block_ctx);
     let (first_action_str, _) = group_data.first().unwrap().clone();
     let (last_action_str, last_block) = group_data.last().unwrap().clone();
@@ -184,7 +502,7 @@ fn GroupedHistoryBlock(
     let last_line_index = group.line_index + group_data.len() - 1;
     
     rsx! {
-        if *is_expanded.read() {
+        if is_expanded_view {
             for (i, (action_str, block)) in group_data.iter().enumerate().take(group_data.len() - 1){
                 ExpressionLine {
                     is_first, is_last: false, is_multiline: false,
@@ -192,21 +510,22 @@ fn GroupedHistoryBlock(
                     block: block.clone(),
                     line_index: group.line_index + i,
                     active_address, is_expanded, address_update_handler, // unused props
-                    ws, seq_index
+                    ws, steps, seq_index, print_mode
                 }
             }
         } 
         ExpressionLine {
-            is_first, is_last, is_multiline,
-            action_str: if *is_expanded.read() { last_action_str } else { first_action_str },
+            is_first, is_last, is_multiline: is_multiline && !print_mode,
+            action_str: if is_expanded_view { last_action_str } else { first_action_str },
             block: last_block,
             line_index: last_line_index,
-            active_address, is_expanded, address_update_handler, ws, seq_index
+            active_address, is_expanded, address_update_handler, ws, steps, seq_index, print_mode
         }
         
     }
 }
 
+#[cfg(not(feature = "cli"))]
 #[component]
 fn ExpressionLine(
     is_first: bool, is_last: bool, is_multiline: bool,
@@ -215,8 +534,29 @@ fn ExpressionLine(
     is_expanded: Signal<bool>,
     address_update_handler: EventHandler<(Address, bool)>,
     line_index: usize, seq_index: usize,
-    ws: Signal<equaio::worksheet::Worksheet>
+    ws: Signal<equaio::worksheet::Worksheet>,
+    steps: Signal<Vec<Step>>,
+    print_mode: bool
 ) -> Element {
+    let mut is_justified = use_signal(|| false);
+    if print_mode {
+        return rsx! {
+            div {
+                class: "expression-line-container print",
+                div {
+                    class: "expression-line-margin-caption",
+                    "{action_str}"
+                }
+                div {
+                    class: "expression-line-left-bar",
+                }
+                div {
+                    class: "expression-line-block",
+                    Block { block, active_address: None, on_address_update: |_| {} }
+                }
+            }
+        };
+    }
     rsx!{
         div {
             class: if is_last {"expression-line-container"} else {"expression-line-container not-last"},
@@ -227,6 +567,17 @@ fn ExpressionLine(
                 div {
                     class: "expression-line-action",
                     "{action_str}"
+                    if line_index > 0 {
+                        button {
+                            class: "expression-line-expand-button justification-button",
+                            title: "Show the rule behind this step",
+                            onclick: move |_| {
+                                let new_value = !*is_justified.peek();
+                                is_justified.set(new_value);
+                            },
+                            if *is_justified.read() { "hide why" } else { "why?" }
+                        }
+                    }
                     if is_multiline {
                         span {
                             class: "expression-line-expand-elipsis",
@@ -250,6 +601,9 @@ fn ExpressionLine(
                         on_address_update: move |evt| address_update_handler.call(evt)
                     }
                 }
+                if *is_justified.read() {
+                    justification::JustificationPanel { line_index, seq_index, ws, steps }
+                }
             }
             if !is_last {
                 div {
@@ -259,6 +613,7 @@ fn ExpressionLine(
                             let mut seq = ws.write().get(seq_index).unwrap();
                             seq.reset_to(line_index);
                             ws.write().store(seq_index, seq);
+                            steps.write().retain(|step| step.seq != seq_index || step.line <= line_index);
                             active_address.write().clear();
                         },
                         "reset"
@@ -269,9 +624,11 @@ fn ExpressionLine(
     }
 }
 
+#[cfg(not(feature = "cli"))]
 #[component]
-fn Block(block: Block, active_address: Option<Signal<Vec<Address>>>, on_address_update: EventHandler<(Address, bool)>) -> Element {
+pub fn Block(block: Block, active_address: Option<Signal<Vec<Address>>>, on_address_update: EventHandler<(Address, bool)>) -> Element {
     use equaio::block::{BlockType, BlockTag};
+    let presentation = try_use_context::<Presentation>().unwrap_or_default();
     let mut classlist = vec![];
     if block.contains_tag(&BlockTag::Parentheses) { classlist.push("parenthesis"); }
     match block.block_type {
@@ -282,20 +639,86 @@ fn Block(block: Block, active_address: Option<Signal<Vec<Address>>>, on_address_
             if is_clickable { classlist.push("clickable"); }
             if is_active { classlist.push("active"); }
             if block.contains_tag(&BlockTag::Concealed) { classlist.push("concealed") };
-            let symbol = utils::convert_mathvar(block.symbol.unwrap_or_default());
+            let symbol = block.symbol.unwrap_or_default();
+            let tokens = match presentation.function_style(&symbol) {
+                Some(FunctionStyle::Radical) => { classlist.push("function-radical"); vec![MathToken::plain("√")] },
+                Some(FunctionStyle::Abs) => { classlist.push("function-abs"); vec![MathToken::plain("|")] },
+                Some(FunctionStyle::Named) => { classlist.push("function-named"); utils::tokenize_math(&symbol, &[]) },
+                None => presentation.display_symbol(&symbol),
+            };
+            let address = address_key(&block.address);
             return rsx! {
                 div {
                     class: classlist.join(" "),
+                    "data-address": address,
                     onclick: move |_| if is_clickable { 
                         on_address_update.call((block.address.clone(), !is_active))
                     },
-                    "{symbol}"
+                    MathTokens { tokens }
                 }
             }
         }
         BlockType::HorizontalContainer => {
-            classlist.push("block-horizontal");
             let children = block.children.unwrap_or_default();
+            if let Some((base, op, exponent)) = as_power_children(&children) {
+                classlist.push("block-power");
+                return rsx! {
+                    div {
+                        class: classlist.join(" "),
+                        Block { block: base, active_address, on_address_update }
+                        Block { block: op, active_address, on_address_update }
+                        div {
+                            class: "block-power-exponent",
+                            Block { block: exponent, active_address, on_address_update }
+                        }
+                    }
+                };
+            }
+            if let Some((base, op, argument)) = as_base_function_children(&children, &presentation) {
+                classlist.push("block-function");
+                let argument_class = if argument.contains_tag(&BlockTag::Parentheses) {
+                    "block-function-argument"
+                } else {
+                    "block-function-argument parenthesized"
+                };
+                return rsx! {
+                    div {
+                        class: classlist.join(" "),
+                        Block { block: op, active_address, on_address_update }
+                        div {
+                            class: "block-function-base",
+                            Block { block: base, active_address, on_address_update }
+                        }
+                        div {
+                            class: argument_class,
+                            Block { block: argument, active_address, on_address_update }
+                        }
+                    }
+                };
+            }
+            if let Some((op, argument, style)) = as_function_children(&children, &presentation) {
+                classlist.push("block-function");
+                let argument_class = match style {
+                    FunctionStyle::Radical => "block-function-argument radicand",
+                    FunctionStyle::Abs => "block-function-argument abs",
+                    FunctionStyle::Named if argument.contains_tag(&BlockTag::Parentheses) => "block-function-argument",
+                    FunctionStyle::Named => "block-function-argument parenthesized",
+                };
+                return rsx! {
+                    div {
+                        class: classlist.join(" "),
+                        Block { block: op, active_address, on_address_update }
+                        div {
+                            class: argument_class,
+                            Block { block: argument, active_address, on_address_update }
+                        }
+                        if style == FunctionStyle::Abs {
+                            div { class: "block-symbol function-abs", "|" }
+                        }
+                    }
+                };
+            }
+            classlist.push("block-horizontal");
             return rsx! {
                 div {
                     class: classlist.join(" "),
@@ -331,4 +754,70 @@ fn Block(block: Block, active_address: Option<Signal<Vec<Address>>>, on_address_
             };
         }
     }
-}
\ No newline at end of file
+}
+
+/// Math text (e.g. a problem sublabel) with the declared variables in italic
+#[cfg(not(feature = "cli"))]
+#[component]
+pub fn MathText(text: String, variables: Vec<String>) -> Element {
+    let tokens = utils::tokenize_math(&text, &variables);
+    rsx! {
+        span {
+            class: "math-text",
+            MathTokens { tokens }
+        }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+#[component]
+fn MathTokens(tokens: Vec<MathToken>) -> Element {
+    rsx! {
+        for token in tokens {
+            span {
+                class: if token.is_variable { "math-variable" } else { "math-constant" },
+                "{token.text}"
+                if let Some(subscript) = token.subscript {
+                    sub { "{subscript}" }
+                }
+            }
+        }
+    }
+}
+
+/// Returns `(base, operator, exponent)` if the children of a horizontal container form `base ^ exponent`
+#[cfg(not(feature = "cli"))]
+fn as_power_children(children: &[Block]) -> Option<(Block, Block, Block)> {
+    use equaio::block::BlockType;
+    if let [base, op, exponent] = children {
+        let is_power_op = matches!(op.block_type, BlockType::Symbol) && op.symbol.as_deref() == Some("^");
+        if is_power_op { return Some((base.clone(), op.clone(), exponent.clone())); }
+    }
+    return None;
+}
+
+/// Returns `(base, operator, argument)` if the children of a horizontal container form `base log argument`
+/// or another operator declared in the `base_functions` of the ruleset presentation
+#[cfg(not(feature = "cli"))]
+fn as_base_function_children(children: &[Block], presentation: &Presentation) -> Option<(Block, Block, Block)> {
+    use equaio::block::BlockType;
+    if let [base, op, argument] = children {
+        if !matches!(op.block_type, BlockType::Symbol) { return None; }
+        if !presentation.is_base_function(op.symbol.as_deref().unwrap_or_default()) { return None; }
+        return Some((base.clone(), op.clone(), argument.clone()));
+    }
+    return None;
+}
+
+/// Returns `(operator, argument, style)` if the children of a horizontal container form a function application
+/// of a function declared in the ruleset presentation
+#[cfg(not(feature = "cli"))]
+fn as_function_children(children: &[Block], presentation: &Presentation) -> Option<(Block, Block, FunctionStyle)> {
+    use equaio::block::BlockType;
+    if let [op, argument] = children {
+        if !matches!(op.block_type, BlockType::Symbol) { return None; }
+        let style = presentation.function_style(op.symbol.as_deref().unwrap_or_default())?;
+        return Some((op.clone(), argument.clone(), style));
+    }
+    return None;
+}